@@ -81,6 +81,18 @@ impl Widget for BytesSizeUi<'_> {
                 };
             });
 
+            ui.horizontal(|ui| {
+                if let Some(BytesSize::Expr { expr }) = bs {
+                    let _ = ui.radio(true, "表达式");
+                    ui.text_edit_singleline(expr)
+                        .on_hover_text("使用`self.field_name`引用之前的同级字段");
+                } else {
+                    if ui.radio(false, "表达式").clicked() {
+                        *bs = Some(BytesSize::expr(""));
+                    }
+                };
+            });
+
             ui.vertical(|ui| {
                 if let Some(BytesSize::Enum { by, map }) = bs {
                     let _ = ui.radio(true, "以指定字段的枚举值");
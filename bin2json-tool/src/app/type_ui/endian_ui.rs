@@ -14,3 +14,19 @@ impl Widget for EndianUi<'_> {
             .response
     }
 }
+
+/// 可以不指定的字节顺序，不指定时使用所在结构体的默认字节顺序
+pub struct UnitEndianUi<'a>(pub &'a mut Option<Endian>);
+
+impl Widget for UnitEndianUi<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let endian = self.0;
+        ui.horizontal(|ui| {
+            ui.radio_value(endian, None, "默认")
+                .on_hover_text("使用所在结构体的默认字节顺序");
+            ui.radio_value(endian, Some(Endian::Big), "大端");
+            ui.radio_value(endian, Some(Endian::Little), "小端");
+        })
+            .response
+    }
+}
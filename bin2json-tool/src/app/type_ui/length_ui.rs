@@ -1,7 +1,7 @@
 use eframe::egui;
 use eframe::egui::{Response, Ui, Widget};
 
-use bin2json::ty::Length;
+use bin2json::ty::{Length, Terminator};
 
 pub struct LengthUi<'a>(pub &'a mut Option<Length>);
 
@@ -36,6 +36,50 @@ impl Widget for LengthUi<'_> {
                     }
                 };
             });
+
+            ui.horizontal(|ui| {
+                if let Some(Length::Expr { expr }) = len {
+                    let _ = ui.radio(true, "表达式");
+                    ui.text_edit_singleline(expr)
+                        .on_hover_text("使用`self.field_name`引用之前的同级字段");
+                } else {
+                    if ui.radio(false, "表达式").clicked() {
+                        *len = Some(Length::expr(""));
+                    }
+                };
+            });
+
+            ui.horizontal(|ui| {
+                if let Some(Length::Until { until, include, consume }) = len {
+                    let _ = ui.radio(true, "终止条件");
+                    let is_value = matches!(until, Terminator::Value(_));
+                    if ui.radio(is_value, "值").clicked() && !is_value {
+                        *until = Terminator::Value(0.into());
+                    }
+                    if ui.radio(!is_value, "表达式").clicked() && is_value {
+                        *until = Terminator::Expr("".to_string());
+                    }
+                    match until {
+                        Terminator::Value(v) => {
+                            if let Some(mut n) = v.as_i64() {
+                                ui.add(egui::DragValue::new(&mut n));
+                                *v = n.into();
+                            } else {
+                                ui.label(v.to_string());
+                            }
+                        }
+                        Terminator::Expr(expr) => {
+                            ui.text_edit_singleline(expr);
+                        }
+                    }
+                    ui.checkbox(include, "包含终止成员");
+                    ui.checkbox(consume, "消耗终止成员");
+                } else {
+                    if ui.radio(false, "终止条件").clicked() {
+                        *len = Some(Length::until(Terminator::Value(0.into())));
+                    }
+                };
+            });
         })
             .response
     }
@@ -1,13 +1,19 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset};
 use eframe::egui;
 use eframe::egui::{Response, Ui};
 
 use bin2json::{range_map, Type};
 use bin2json::range::{KeyRange, KeyRangeMap};
 use bin2json::secure::SecureKey;
-use bin2json::ty::{BytesSize, Checksum, Endian, Field};
+use bin2json::ty::{
+    BitOrder, BitSize, BytesSize, Checksum, Compression, Encoding, Endian, Epoch, Field, FlagsFormat,
+    PointerBase, Resolution, Rounding, TlvFormat, Unit,
+};
 pub use bytes_size_ui::BytesSizeUi;
 pub use converter_ui::ConverterUi;
-pub use endian_ui::EndianUi;
+pub use endian_ui::{EndianUi, UnitEndianUi};
 pub use length_ui::LengthUi;
 pub use raw_edit_ui::RawEditUi;
 pub use secure_key_ui::SecureKeyUi;
@@ -34,6 +40,14 @@ pub struct TypeUi {
     temp_fields: Vec<(String, TypeUi)>,
 
     temp_enum_error: String,
+
+    temp_mapping_name: String,
+    temp_mapping_key: String,
+
+    /// 作为结构字段时的存在条件
+    field_condition: String,
+    /// 作为结构字段时的断言
+    field_assert: String,
 }
 
 
@@ -48,6 +62,10 @@ impl TypeUi {
             temp_bs_error: "".to_string(),
             ident,
             temp_enum_error: "".to_string(),
+            temp_mapping_name: "".to_string(),
+            temp_mapping_key: "".to_string(),
+            field_condition: "".to_string(),
+            field_assert: "".to_string(),
             ident_counter: 0,
         }
     }
@@ -61,12 +79,19 @@ impl TypeUi {
         self.ident_counter = 0;
 
         self.temp_enum_error.clear();
+        self.temp_mapping_name.clear();
+        self.temp_mapping_key.clear();
 
         if let
         | Type::Array { .. }
+        | Type::Mapping { .. }
+        | Type::Timestamp { .. }
         | Type::Converter { .. }
         | Type::Encrypt { .. }
+        | Type::Compressed { .. }
+        | Type::Pointer { .. }
         | Type::Enum { .. }
+        | Type::Tlv { .. }
         = self.ty {
             self.add_temp_field("", None);
         }
@@ -75,13 +100,20 @@ impl TypeUi {
     fn set_type(&mut self, ty: Type) {
         self.ty = ty;
         self.reset_state();
+        let mut conditions = vec![];
         let fields: Vec<_> = match &self.ty {
             Type::Struct { fields, .. } => {
+                conditions = fields.iter()
+                    .map(|f| (
+                        f.condition.clone().unwrap_or_default(),
+                        f.assert.clone().unwrap_or_default(),
+                    ))
+                    .collect();
                 fields.iter()
-                    .map(|Field { name, ty }| (name.clone(), Some(ty.clone())))
+                    .map(|Field { name, ty, .. }| (name.clone(), Some(ty.clone())))
                     .collect()
             }
-            Type::Enum { map, .. } => {
+            Type::Enum { map, .. } | Type::Tlv { values: map, .. } => {
                 let mut l = map.iter()
                     .map(|(k, v)| (k.to_string(), Some(v.clone())))
                     .collect::<Vec<_>>();
@@ -89,16 +121,24 @@ impl TypeUi {
                 l
             }
             | Type::Array { element_type: ty, .. }
+            | Type::Mapping { original_type: ty, .. }
+            | Type::Timestamp { original_type: ty, .. }
             | Type::Converter { original_type: ty, .. }
             | Type::Encrypt { inner_type: ty, .. }
+            | Type::Compressed { inner_type: ty, .. }
+            | Type::Pointer { target: ty, .. }
             => vec![("".to_string(), Some(ty.as_ref().clone()))],
             _ => vec![],
         };
         if !fields.is_empty() {
             self.temp_fields.clear();
             self.ident_counter = 0;
-            for (name, ty) in fields {
-                self.add_temp_field(name, ty);
+            for (idx, (name, ty)) in fields.into_iter().enumerate() {
+                let (_, tui) = self.add_temp_field(name, ty);
+                if let Some((condition, assert)) = conditions.get_mut(idx) {
+                    tui.field_condition = std::mem::take(condition);
+                    tui.field_assert = std::mem::take(assert);
+                }
             }
         }
     }
@@ -154,6 +194,9 @@ impl TypeUi {
                     temp_bs_error,
                     temp_fields,
                     temp_enum_error,
+                    temp_mapping_name,
+                    temp_mapping_key,
+                    ..
                 } = self;
 
                 match ty {
@@ -161,6 +204,24 @@ impl TypeUi {
                         ui.label("魔法值");
                         ui.add(RawEditUi::new(magic, false));
                     }
+                    Type::Padding { size, fill } => {
+                        ui.label("大小");
+                        ui.add(egui::DragValue::new(&mut size.0).suffix("比特"));
+                        ui.end_row();
+
+                        ui.label("填充内容");
+                        ui.add(RawEditUi::new(fill, false));
+                        ui.end_row();
+                    }
+                    Type::Align { align, fill } => {
+                        ui.label("对齐大小");
+                        ui.add(egui::DragValue::new(&mut align.0).suffix("比特"));
+                        ui.end_row();
+
+                        ui.label("填充内容");
+                        ui.add(RawEditUi::new(fill, false));
+                        ui.end_row();
+                    }
                     Type::Boolean { bit } => {
                         ui.label("大小");
                         ui.radio_value(bit, true, "1 比特位");
@@ -172,13 +233,15 @@ impl TypeUi {
                     | Type::Int16 { unit }
                     | Type::Int32 { unit }
                     | Type::Int64 { unit }
+                    | Type::Int128 { unit }
                     | Type::Uint8 { unit }
                     | Type::Uint16 { unit }
                     | Type::Uint32 { unit }
                     | Type::Uint64 { unit }
+                    | Type::Uint128 { unit }
                     => {
                         ui.label("字节顺序");
-                        ui.add(EndianUi(&mut unit.endian));
+                        ui.add(UnitEndianUi(&mut unit.endian));
                         ui.end_row();
 
                         ui.label("总大小");
@@ -186,29 +249,151 @@ impl TypeUi {
                         ui.end_row();
                     }
 
+                    Type::VarInt { signed, zigzag } => {
+                        ui.label("有符号");
+                        ui.checkbox(signed, "");
+                        ui.end_row();
+
+                        if *signed {
+                            ui.label("ZigZag编码");
+                            ui.checkbox(zigzag, "");
+                            ui.end_row();
+                        }
+                    }
+
+                    | Type::Float16 { endian }
+                    | Type::BFloat16 { endian }
                     | Type::Float32 { endian }
                     | Type::Float64 { endian }
                     => {
+                        ui.label("字节顺序");
+                        ui.add(UnitEndianUi(endian));
+                        ui.end_row();
+                    }
+
+                    Type::Fixed {
+                        signed,
+                        integer_bits,
+                        fraction_bits,
+                        unit,
+                        rounding,
+                    } => {
+                        ui_fixed(ui, ident, signed, integer_bits, fraction_bits, unit, rounding);
+                    }
+
+                    Type::Bcd {
+                        digits,
+                        unpacked,
+                        signed,
+                        endian,
+                        as_string,
+                    } => {
+                        ui.label("数字位数");
+                        ui.add(egui::DragValue::new(digits).suffix("位"));
+                        ui.end_row();
+
+                        ui.label("非压缩");
+                        ui.checkbox(unpacked, "");
+                        ui.end_row();
+
+                        ui.label("带符号");
+                        ui.checkbox(signed, "");
+                        ui.end_row();
+
+                        ui.label("字节顺序");
+                        ui.add(UnitEndianUi(endian));
+                        ui.end_row();
+
+                        ui.label("输出为字符串");
+                        ui.checkbox(as_string, "");
+                        ui.end_row();
+                    }
+
+                    Type::Flags { unit, flags, format } => {
+                        ui.label("字节顺序");
+                        ui.add(UnitEndianUi(&mut unit.endian));
+                        ui.end_row();
+
+                        ui.label("总大小");
+                        ui.add(SizeUi(&mut unit.size));
+                        ui.end_row();
+
+                        ui.label("输出格式");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(format, FlagsFormat::List, "名称数组");
+                            ui.radio_value(format, FlagsFormat::Object, "对象");
+                        });
+                        ui.end_row();
+
+                        ui_flags(ui, ident, temp_bs_enum_value, temp_mapping_name, flags);
+                    }
+
+                    Type::Ipv4 | Type::Ipv6 | Type::Mac => {}
+
+                    Type::Uuid { endian } => {
                         ui.label("字节顺序");
                         ui.add(EndianUi(endian));
                         ui.end_row();
                     }
 
-                    | Type::String { size }
-                    | Type::Bin { size }
-                    => {
+                    Type::String {
+                        size,
+                        encoding,
+                        lossy,
+                        padding,
+                    } => {
                         ui.label("大小");
                         ui.add(BytesSizeUi::new(
                             size,
                             temp_bs_enum_key,
                             temp_bs_enum_value,
                             temp_bs_error,
-                            format!("{} > Bin/String", ident),
+                            format!("{} > String", ident),
                         ));
                         ui.end_row();
+
+                        ui.label("编码");
+                        egui::ComboBox::from_id_source(format!("{} > String Encoding ComboBox", ident))
+                            .selected_text(encoding.name())
+                            .show_ui(ui, |ui| {
+                                for e in [
+                                    Encoding::Utf8,
+                                    Encoding::Ascii,
+                                    Encoding::Latin1,
+                                    Encoding::Utf16Le,
+                                    Encoding::Utf16Be,
+                                    Encoding::Utf32Le,
+                                    Encoding::Utf32Be,
+                                    Encoding::Gbk,
+                                    Encoding::Gb18030,
+                                ] {
+                                    ui.selectable_value(encoding, e, e.name());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("替换无效字符");
+                        ui.checkbox(lossy, "");
+                        ui.end_row();
+
+                        ui_padding(ui, padding);
+                    }
+
+                    Type::Bin { size, padding } => {
+                        ui.label("大小");
+                        ui.add(BytesSizeUi::new(
+                            size,
+                            temp_bs_enum_key,
+                            temp_bs_enum_value,
+                            temp_bs_error,
+                            format!("{} > Bin", ident),
+                        ));
+                        ui.end_row();
+
+                        ui_padding(ui, padding);
                     }
 
-                    Type::Struct { size, fields } => {
+                    Type::Struct { size, fields, default_endian, bit_order, .. } => {
                         ui_struct(
                             ui,
                             ident,
@@ -218,6 +403,8 @@ impl TypeUi {
                             temp_bs_error,
                             temp_fields,
                             size,
+                            default_endian,
+                            bit_order,
                             fields,
                         );
                     }
@@ -246,6 +433,12 @@ impl TypeUi {
                         ui.end_row();
                     }
 
+                    Type::Ref { name } => {
+                        ui.label("引用名称").on_hover_text("外层结构体中类型定义的名称");
+                        ui.text_edit_singleline(name);
+                        ui.end_row();
+                    }
+
                     Type::Enum { by, map, size } => {
                         if ui_enum(
                             ui,
@@ -263,6 +456,74 @@ impl TypeUi {
                         }
                     }
 
+                    Type::Mapping { original_type, map } => {
+                        let (_, ty_ui) = last_field(temp_fields);
+                        ui.label("原始类型");
+                        ui.horizontal_top(|ui| ty_ui.ui(ui));
+                        ui.end_row();
+                        **original_type = ty_ui.ty.clone();
+
+                        ui_mapping(ui, ident, temp_bs_enum_key, temp_mapping_name, temp_enum_error, map);
+                    }
+
+                    Type::Tlv {
+                        tag,
+                        length,
+                        values,
+                        names,
+                        format,
+                        size,
+                    } => {
+                        ui.label("大小");
+                        ui.add(BytesSizeUi::new(
+                            size,
+                            temp_bs_enum_key,
+                            temp_bs_enum_value,
+                            temp_bs_error,
+                            format!("{} > Tlv", ident),
+                        ));
+                        ui.end_row();
+
+                        ui.label("标签类型");
+                        ui_int_type(ui, &format!("{} > Tlv tag", ident), tag);
+                        ui.end_row();
+
+                        ui.label("长度类型");
+                        ui_int_type(ui, &format!("{} > Tlv length", ident), length);
+                        ui.end_row();
+
+                        ui.label("输出格式");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(format, TlvFormat::List, "对象数组");
+                            ui.radio_value(format, TlvFormat::Object, "对象");
+                        });
+                        ui.end_row();
+
+                        ui_mapping(ui, ident, temp_mapping_key, temp_mapping_name, temp_enum_error, names);
+
+                        ui.label("值类型");
+                        let add = ui_type_map(ui, &format!("{} > Tlv", ident), "标签", temp_fields, temp_enum_error, values);
+                        ui.end_row();
+                        if add {
+                            self.add_temp_field("", None);
+                        }
+                    }
+
+                    Type::Timestamp {
+                        original_type,
+                        epoch,
+                        resolution,
+                        timezone,
+                    } => {
+                        let (_, ty_ui) = last_field(temp_fields);
+                        ui.label("原始类型");
+                        ui.horizontal_top(|ui| ty_ui.ui(ui));
+                        ui.end_row();
+                        **original_type = ty_ui.ty.clone();
+
+                        ui_timestamp(ui, ident, epoch, resolution, timezone);
+                    }
+
                     Type::Converter {
                         original_type,
                         on_read,
@@ -283,6 +544,35 @@ impl TypeUi {
                         *original_type = Box::new(ty_ui.ty.clone());
                     }
 
+                    Type::Computed { expr } => {
+                        ui.label("表达式").on_hover_text("使用`self.field_name`引用之前的同级字段，写入时忽略本字段");
+                        ui.text_edit_singleline(expr);
+                        ui.end_row();
+                    }
+
+                    Type::Pointer {
+                        offset_type,
+                        target,
+                        base,
+                    } => {
+                        ui.label("偏移量类型");
+                        ui_int_type(ui, &format!("{} > Pointer offset", ident), offset_type);
+                        ui.end_row();
+
+                        ui.label("偏移量起始位置");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(base, PointerBase::Buffer, "数据流");
+                            ui.radio_value(base, PointerBase::Struct, "所在结构体");
+                        });
+                        ui.end_row();
+
+                        let (_, ty_ui) = last_field(temp_fields);
+                        ui.label("目标类型");
+                        ui.horizontal_top(|ui| ty_ui.ui(ui));
+                        ui.end_row();
+                        **target = ty_ui.ty.clone();
+                    }
+
                     Type::Checksum {
                         method,
                         start_key,
@@ -310,6 +600,23 @@ impl TypeUi {
                             size,
                         );
                     }
+                    Type::Compressed {
+                        algorithm,
+                        inner_type,
+                        size,
+                    } => {
+                        ui_compressed(
+                            ui,
+                            ident,
+                            temp_fields,
+                            temp_bs_enum_key,
+                            temp_bs_enum_value,
+                            temp_bs_error,
+                            algorithm,
+                            inner_type,
+                            size,
+                        );
+                    }
                     Type::Sign {
                         on_read,
                         on_write,
@@ -336,7 +643,7 @@ impl TypeUi {
     }
 }
 
-fn ui_struct(ui: &mut Ui, parent_id: &mut String, parent_id_counter: &mut usize, temp_bs_enum_key: &mut String, temp_bs_enum_value: &mut usize, temp_bs_error: &mut String, temp_fields: &mut Vec<(String, TypeUi)>, size: &mut Option<BytesSize>, fields: &mut Vec<Field>) {
+fn ui_struct(ui: &mut Ui, parent_id: &mut String, parent_id_counter: &mut usize, temp_bs_enum_key: &mut String, temp_bs_enum_value: &mut usize, temp_bs_error: &mut String, temp_fields: &mut Vec<(String, TypeUi)>, size: &mut Option<BytesSize>, default_endian: &mut Option<Endian>, bit_order: &mut Option<BitOrder>, fields: &mut Vec<Field>) {
     ui.label("大小");
     ui.add(BytesSizeUi::new(
         size,
@@ -347,6 +654,19 @@ fn ui_struct(ui: &mut Ui, parent_id: &mut String, parent_id_counter: &mut usize,
     ));
     ui.end_row();
 
+    ui.label("默认字节顺序").on_hover_text("未指定字节顺序的字段使用的字节顺序");
+    ui.add(UnitEndianUi(default_endian));
+    ui.end_row();
+
+    ui.label("位顺序").on_hover_text("按位排列的字段在字节内的顺序");
+    ui.horizontal(|ui| {
+        ui.radio_value(bit_order, None, "默认")
+            .on_hover_text("使用外层结构体的位顺序");
+        ui.radio_value(bit_order, Some(BitOrder::Msb0), "高位在前");
+        ui.radio_value(bit_order, Some(BitOrder::Lsb0), "低位在前");
+    });
+    ui.end_row();
+
     ui.horizontal(|ui| {
         ui.label("字段列表");
         if ui.button("+").on_hover_text("添加字段").clicked() {
@@ -371,7 +691,15 @@ fn ui_struct(ui: &mut Ui, parent_id: &mut String, parent_id_counter: &mut usize,
                 ui.separator();
                 ui.end_row();
 
-                ui.text_edit_singleline(name);
+                ui.vertical(|ui| {
+                    ui.text_edit_singleline(name);
+                    ui.label("存在条件");
+                    ui.text_edit_singleline(&mut ty.field_condition)
+                        .on_hover_text("为空时字段总是存在。如：self.has_ext");
+                    ui.label("断言");
+                    ui.text_edit_singleline(&mut ty.field_assert)
+                        .on_hover_text("为空时不检查。如：self.payload_len <= self.total_len - 8");
+                });
                 ty.ui(ui);
                 if ui.button("删除").clicked() {
                     remove_list.push(idx);
@@ -386,7 +714,14 @@ fn ui_struct(ui: &mut Ui, parent_id: &mut String, parent_id_counter: &mut usize,
             fields.clear();
             fields.extend(temp_fields.iter()
                 .map(|(name, ty)| {
-                    Field::new(name, ty.ty.clone())
+                    let mut field = Field::new(name, ty.ty.clone());
+                    if !ty.field_condition.trim().is_empty() {
+                        field = field.with_condition(ty.field_condition.trim());
+                    }
+                    if !ty.field_assert.trim().is_empty() {
+                        field = field.with_assert(ty.field_assert.trim());
+                    }
+                    field
                 }));
         });
     ui.end_row();
@@ -405,7 +740,6 @@ fn ui_enum(
     map: &mut KeyRangeMap<Type>,
     size: &mut Option<BytesSize>,
 ) -> bool {
-    let mut add = false;
     ui.label("大小");
     ui.add(BytesSizeUi::new(
         size,
@@ -421,10 +755,25 @@ fn ui_enum(
     ui.end_row();
 
     ui.label("枚举值");
+    let add = ui_type_map(ui, "Enum", "引用字段值", temp_fields, temp_enum_error, map);
+    ui.end_row();
+    add
+}
+
+/// 编辑数值范围与类型的映射，返回是否需要添加新的类型
+fn ui_type_map(
+    ui: &mut Ui,
+    grid_id: &str,
+    key_label: &str,
+    temp_fields: &mut Vec<(String, TypeUi)>,
+    temp_enum_error: &mut String,
+    map: &mut KeyRangeMap<Type>,
+) -> bool {
+    let mut add = false;
     ui.vertical(|ui| {
-        egui::Grid::new("Enum")
+        egui::Grid::new(grid_id)
             .show(ui, |ui| {
-                ui.label("引用字段值");
+                ui.label(key_label);
                 ui.label("类型");
                 ui.label("操作");
                 ui.end_row();
@@ -505,10 +854,240 @@ fn ui_enum(
                 }
             });
     });
-    ui.end_row();
     add
 }
 
+fn ui_mapping(
+    ui: &mut Ui,
+    parent_id: &str,
+    temp_kr: &mut String,
+    temp_name: &mut String,
+    error: &mut String,
+    map: &mut KeyRangeMap<String>,
+) {
+    ui.label("映射");
+    egui::Grid::new(format!("{} > Mapping", parent_id))
+        .show(ui, |ui| {
+            ui.label("数值");
+            ui.label("名称");
+            ui.label("操作");
+            ui.end_row();
+
+            map.retain(|k, v| {
+                ui.label(k.to_string());
+                ui.text_edit_singleline(v);
+                let r = !ui.button("删除").clicked();
+                ui.end_row();
+                r
+            });
+
+            let resp = ui.text_edit_singleline(temp_kr)
+                .on_hover_text(KEY_RANGE_FORMAT);
+            if !error.is_empty() {
+                let pid = ui.make_persistent_id("mapping error");
+                ui.memory().open_popup(pid);
+                egui::popup_below_widget(ui, pid, &resp, |ui| {
+                    if ui.add(
+                        egui::Label::new(egui::RichText::new(error.as_str()).color(egui::Color32::RED))
+                            .wrap(false)
+                            .sense(egui::Sense::click())
+                    ).clicked() {
+                        *error = Default::default();
+                    }
+                });
+            }
+
+            ui.text_edit_singleline(temp_name);
+            if ui.button("添加/修改").clicked() {
+                match temp_kr.parse::<KeyRange>() {
+                    Ok(kr) => {
+                        map.insert(kr, std::mem::take(temp_name));
+                        *temp_kr = Default::default();
+                        *error = Default::default();
+                    }
+                    Err(_) => {
+                        *error = format!("输入格式错误\n{}", KEY_RANGE_FORMAT);
+                    }
+                }
+            }
+        });
+    ui.end_row();
+}
+
+/// 选择整数类型
+fn ui_int_type(ui: &mut Ui, id: &str, ty: &mut Type) {
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(format!("{} ComboBox", id))
+            .selected_text(ty.type_name())
+            .show_ui(ui, |ui| {
+                for t in [
+                    Type::uint8(),
+                    Type::uint16(Endian::Big),
+                    Type::uint32(Endian::Big),
+                    Type::var_int(false, false),
+                ] {
+                    let checked = ty.type_name() == t.type_name();
+                    if ui.selectable_label(checked, t.type_name()).clicked() && !checked {
+                        *ty = t;
+                    }
+                }
+            });
+        if let Type::Uint16 { unit } | Type::Uint32 { unit } = ty {
+            ui.add(UnitEndianUi(&mut unit.endian));
+        }
+    });
+}
+
+fn ui_flags(
+    ui: &mut Ui,
+    parent_id: &str,
+    temp_bit: &mut usize,
+    temp_name: &mut String,
+    flags: &mut BTreeMap<u8, String>,
+) {
+    ui.label("标志");
+    egui::Grid::new(format!("{} > Flags", parent_id))
+        .show(ui, |ui| {
+            ui.label("比特位");
+            ui.label("名称");
+            ui.label("操作");
+            ui.end_row();
+
+            flags.retain(|bit, name| {
+                ui.label(bit.to_string());
+                ui.text_edit_singleline(name);
+                let r = !ui.button("删除").clicked();
+                ui.end_row();
+                r
+            });
+
+            ui.add(egui::DragValue::new(temp_bit).clamp_range(0..=63));
+            ui.text_edit_singleline(temp_name);
+            if ui.button("添加/修改").clicked() {
+                flags.insert(*temp_bit as u8, std::mem::take(temp_name));
+                *temp_bit = 0;
+            }
+        });
+    ui.end_row();
+}
+
+fn ui_timestamp(
+    ui: &mut Ui,
+    parent_id: &str,
+    epoch: &mut Epoch,
+    resolution: &mut Resolution,
+    timezone: &mut Option<FixedOffset>,
+) {
+    ui.label("起始时间");
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(format!("{} > Timestamp Epoch ComboBox", parent_id))
+            .selected_text(epoch.name())
+            .show_ui(ui, |ui| {
+                for e in [
+                    Epoch::Unix,
+                    Epoch::Gps,
+                    Epoch::Ntp,
+                    Epoch::Windows,
+                    Epoch::Custom(DateTime::UNIX_EPOCH),
+                ] {
+                    let checked = std::mem::discriminant(epoch) == std::mem::discriminant(&e);
+                    if ui.selectable_label(checked, e.name()).clicked() && !checked {
+                        *epoch = e;
+                    }
+                }
+            });
+        if let Epoch::Custom(dt) = epoch {
+            let mut secs = dt.timestamp();
+            ui.add(egui::DragValue::new(&mut secs).prefix("Unix时间戳: ").suffix("秒"));
+            if let Some(new) = DateTime::from_timestamp(secs, 0) {
+                *dt = new;
+            }
+        }
+    });
+    ui.end_row();
+
+    ui.label("精度");
+    egui::ComboBox::from_id_source(format!("{} > Timestamp Resolution ComboBox", parent_id))
+        .selected_text(resolution.name())
+        .show_ui(ui, |ui| {
+            for r in [
+                Resolution::Seconds,
+                Resolution::Milliseconds,
+                Resolution::Microseconds,
+                Resolution::HundredNanoseconds,
+                Resolution::Nanoseconds,
+            ] {
+                ui.selectable_value(resolution, r, r.name());
+            }
+        });
+    ui.end_row();
+
+    ui.label("时区");
+    ui.horizontal(|ui| {
+        let mut enable = timezone.is_some();
+        ui.checkbox(&mut enable, "");
+        if enable {
+            let mut minutes = timezone.map_or(0, |tz| tz.local_minus_utc() / 60);
+            ui.add(
+                egui::DragValue::new(&mut minutes)
+                    .clamp_range(-24 * 60 + 1..=24 * 60 - 1)
+                    .prefix("UTC偏移: ")
+                    .suffix("分钟"),
+            );
+            *timezone = FixedOffset::east_opt(minutes * 60);
+        } else {
+            *timezone = None;
+        }
+    });
+    ui.end_row();
+}
+
+fn ui_fixed(
+    ui: &mut Ui,
+    parent_id: &str,
+    signed: &mut bool,
+    integer_bits: &mut usize,
+    fraction_bits: &mut usize,
+    unit: &mut Unit,
+    rounding: &mut Rounding,
+) {
+    ui.label("有符号");
+    ui.checkbox(signed, "");
+    ui.end_row();
+
+    ui.label("整数部分");
+    ui.add(egui::DragValue::new(integer_bits).suffix("比特"));
+    ui.end_row();
+
+    ui.label("小数部分");
+    ui.add(egui::DragValue::new(fraction_bits).suffix("比特"));
+    ui.end_row();
+
+    ui.label("字节顺序");
+    ui.add(UnitEndianUi(&mut unit.endian));
+    ui.end_row();
+
+    ui.label("总大小");
+    ui.add(SizeUi(&mut unit.size));
+    ui.end_row();
+
+    ui.label("取整方式");
+    egui::ComboBox::from_id_source(format!("{} > Fixed Rounding ComboBox", parent_id))
+        .selected_text(rounding.name())
+        .show_ui(ui, |ui| {
+            for r in [
+                Rounding::Nearest,
+                Rounding::NearestEven,
+                Rounding::Floor,
+                Rounding::Ceil,
+                Rounding::Truncate,
+            ] {
+                ui.selectable_value(rounding, r, r.name());
+            }
+        });
+    ui.end_row();
+}
+
 fn ui_checksum(
     ui: &mut Ui,
     parent_id: &str,
@@ -580,6 +1159,50 @@ fn ui_encrypt(
     *inner_type = Box::new(ty_ui.ty.clone());
 }
 
+fn ui_compressed(
+    ui: &mut Ui,
+    parent_id: &str,
+    temp_fields: &mut Vec<(String, TypeUi)>,
+    temp_bs_enum_key: &mut String,
+    temp_bs_enum_value: &mut usize,
+    temp_bs_error: &mut String,
+    algorithm: &mut Compression,
+    inner_type: &mut Box<Type>,
+    size: &mut Option<BytesSize>,
+) {
+    ui.label("压缩算法");
+    egui::ComboBox::from_id_source(format!("{} > Compressed ComboBox", parent_id))
+        .selected_text(algorithm.name())
+        .show_ui(ui, |ui| {
+            for a in [
+                Compression::Deflate,
+                Compression::Zlib,
+                Compression::Gzip,
+                Compression::Lz4,
+                Compression::Zstd,
+            ] {
+                ui.selectable_value(algorithm, a, a.name());
+            }
+        });
+    ui.end_row();
+
+    ui.label("大小");
+    ui.add(BytesSizeUi::new(
+        size,
+        temp_bs_enum_key,
+        temp_bs_enum_value,
+        temp_bs_error,
+        format!("{} > Compressed", parent_id),
+    ));
+    ui.end_row();
+
+    let (_, ty_ui) = last_field(temp_fields);
+    ui.label("内部数据类型");
+    ui.horizontal_top(|ui| ty_ui.ui(ui));
+    ui.end_row();
+    **inner_type = ty_ui.ty.clone();
+}
+
 fn ui_sign(
     ui: &mut Ui,
     parent_id: &str,
@@ -635,28 +1258,73 @@ fn last_field(temp_fields: &mut Vec<(String, TypeUi)>) -> &mut (String, TypeUi)
 }
 
 
+fn ui_padding(ui: &mut Ui, padding: &mut Option<u8>) {
+    ui.label("填充字节");
+    ui.horizontal(|ui| {
+        let mut enable = padding.is_some();
+        ui.checkbox(&mut enable, "");
+        match (enable, padding.as_mut()) {
+            (true, Some(p)) => {
+                ui.add(egui::DragValue::new(p).hexadecimal(2, false, true));
+            }
+            (true, None) => *padding = Some(0),
+            (false, _) => *padding = None,
+        }
+    });
+    ui.end_row();
+}
+
 fn default_types() -> Vec<Type> {
     vec![
         Type::magic(b""),
+        Type::padding(BitSize(8)),
+        Type::align(BitSize(32)),
         Type::BOOL,
         Type::int8(),
         Type::int16(Endian::Big),
         Type::int32(Endian::Big),
         Type::int64(Endian::Big),
+        Type::int128(Endian::Big),
         Type::uint8(),
         Type::uint16(Endian::Big),
         Type::uint32(Endian::Big),
         Type::uint64(Endian::Big),
+        Type::uint128(Endian::Big),
+        Type::var_int(false, false),
+        Type::float16(Endian::Big),
+        Type::bfloat16(Endian::Big),
         Type::float32(Endian::Big),
         Type::float64(Endian::Big),
-        Type::String { size: None },
-        Type::Bin { size: None },
+        Type::fixed(true, 16, 16, Endian::Big),
+        Type::bcd(2, Endian::Big),
+        Type::flags(Unit::default(), Vec::<(u8, String)>::new()),
+        Type::Ipv4,
+        Type::Ipv6,
+        Type::Mac,
+        Type::uuid(Endian::Big),
+        Type::String {
+            size: None,
+            encoding: Encoding::Utf8,
+            lossy: false,
+            padding: None,
+        },
+        Type::Bin {
+            size: None,
+            padding: None,
+        },
         Type::new_struct(vec![]),
         Type::new_array(Type::uint8()),
+        Type::new_ref(""),
         Type::new_enum("", range_map! {}),
+        Type::mapping(Type::uint8(), range_map! {}),
+        Type::tlv(Type::uint8(), Type::uint8(), range_map! {}),
+        Type::timestamp(Type::uint32(Endian::Big), Epoch::Unix, Resolution::Seconds),
         Type::converter(Type::uint8(), "self", "self"),
+        Type::computed(""),
+        Type::pointer(Type::uint32(Endian::Big), Type::uint8(), PointerBase::Buffer),
         Type::checksum(Checksum::Xor, ""),
         Type::encrypt(Type::uint8(), SecureKey::None, SecureKey::None),
+        Type::compressed(Type::uint8(), Compression::Zlib),
         Type::sign("", SecureKey::None, SecureKey::None),
     ]
 }
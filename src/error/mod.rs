@@ -1,8 +1,10 @@
 pub use evalexpr::EvalexprError;
 
 pub use read::ReadBinError;
+pub use ref_error::RefError;
 pub use write::WriteBinError;
 
 mod read;
+mod ref_error;
 mod write;
 
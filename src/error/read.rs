@@ -3,6 +3,8 @@ use std::string::FromUtf8Error;
 
 use deku::DekuError;
 
+use crate::error::RefError;
+
 #[derive(Debug, Error, PartialEq)]
 pub enum ReadBinError {
     #[error("魔法值({0:?})不对应")]
@@ -29,6 +31,26 @@ pub enum ReadBinError {
     DecryptError(String),
     #[error("签名验证失败: {0}")]
     VerifyError(String),
+    #[error("解压失败: {0}")]
+    DecompressError(String),
+    #[error("变长整数超出64位范围")]
+    VarIntOverflow,
+    #[error("BCD码({0:?})无效")]
+    BcdError(Vec<u8>),
+    #[error("输入数据不是合法的{0}字符串")]
+    DecodeError(&'static str),
+    #[error("无效的时间戳: {0}")]
+    TimestampError(String),
+    #[error("TLV数据无效: {0}")]
+    TlvError(String),
+    #[error("{0}")]
+    RefError(#[from] RefError),
+    #[error("数组终止成员错误: {0}")]
+    TerminatorError(&'static str),
+    #[error("无效的偏移量: {0}")]
+    PointerError(String),
+    #[error("字段`{field}`的断言`{expr}`不成立")]
+    AssertionFailed { field: String, expr: String },
 }
 
 impl From<FromUtf8Error> for ReadBinError {
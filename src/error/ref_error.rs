@@ -0,0 +1,15 @@
+use crate::ty::MAX_REF_DEPTH;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RefError {
+    #[error("未找到引用的类型定义`{0}`")]
+    NotFound(String),
+    #[error("引用类型的嵌套层数超过{}", MAX_REF_DEPTH)]
+    TooDeep,
+}
+
+impl From<RefError> for evalexpr::EvalexprError {
+    fn from(e: RefError) -> Self {
+        evalexpr::EvalexprError::CustomMessage(e.to_string())
+    }
+}
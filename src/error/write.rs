@@ -1,5 +1,7 @@
 use deku::DekuError;
 
+use crate::error::RefError;
+
 #[derive(Debug, PartialEq, Error)]
 pub enum WriteBinError {
     #[error("Deku错误: {0}")]
@@ -28,4 +30,28 @@ pub enum WriteBinError {
     EncryptError(String),
     #[error("签名失败: {0}")]
     SignError(String),
+    #[error("压缩失败: {0}")]
+    CompressError(String),
+    #[error("输入值({0})不是有效的BCD数字")]
+    BcdError(String),
+    #[error("输入字符串无法使用{0}编码")]
+    EncodeError(&'static str),
+    #[error("映射中未找到名称`{0}`对应的唯一数值")]
+    MappingError(String),
+    #[error("未定义的标志名称`{0}`")]
+    UnknownFlag(String),
+    #[error("输入值({0})不是有效的RFC 3339格式时间")]
+    TimestampError(String),
+    #[error("输入值({1})不是有效的{0}")]
+    FormatError(&'static str, String),
+    #[error("{0}")]
+    RefError(#[from] RefError),
+    #[error("数组终止成员错误: {0}")]
+    TerminatorError(&'static str),
+    #[error("表达式`{expr}`的结果({need})与输入值的大小或长度({input})不一致")]
+    ExprSizeError { expr: String, need: f64, input: usize },
+    #[error("指针写入失败: {0}")]
+    PointerError(&'static str),
+    #[error("字段`{field}`的断言`{expr}`不成立")]
+    AssertionFailed { field: String, expr: String },
 }
@@ -7,9 +7,13 @@ use rsa::pkcs8::LineEnding;
 use rsa::{Pkcs1v15Encrypt, PublicKey, PublicKeyParts, RsaPrivateKey};
 use serde_json::json;
 
+use crate::error::WriteBinError;
 use crate::secure::{Hasher, SecureKey};
-use crate::ty::{BytesSize, Checksum, Endian, Field, Length, Unit};
-use crate::{range_map, Type};
+use crate::ty::{
+    BitOrder, BytesSize, Checksum, Compression, Encoding, Endian, Epoch, Field, FlagsFormat, Length, Options,
+    PointerBase, Resolution, Rounding, Terminator, TlvFormat, Unit,
+};
+use crate::{range_map, ReadBinError, Type};
 
 #[test]
 fn test_convert_enum() {
@@ -289,6 +293,22 @@ fn test_write_num() {
     assert_eq!(123456u64.to_be_bytes().view_bits::<Msb0>(), out);
     assert!(t_u64.write(&json!(-1)).is_err());
     assert!(t_u64.write(&json!(-5.0)).is_err());
+    let big = (1u64 << 53) + 1;
+    let out = t_u64.write(&json!(big)).unwrap();
+    assert_eq!(big.to_be_bytes().view_bits::<Msb0>(), out);
+    assert_eq!(t_u64.read(&out).unwrap().0, json!(big));
+    let out = t_u64.write(&json!(u64::MAX)).unwrap();
+    assert_eq!(u64::MAX.to_be_bytes().view_bits::<Msb0>(), out);
+
+    let t_i64 = Type::int64(Endian::Little);
+    let out = t_i64.write(&json!(i64::MIN + 1)).unwrap();
+    assert_eq!((i64::MIN + 1).to_le_bytes().view_bits::<Msb0>(), out);
+    assert_eq!(t_i64.read(&out).unwrap().0, json!(i64::MIN + 1));
+    assert!(t_i64.write(&json!(u64::MAX)).is_err());
+    assert_eq!(
+        5i64.to_le_bytes().view_bits::<Msb0>(),
+        t_i64.write(&json!(5.0)).unwrap()
+    );
 
     let t_f32 = Type::float32(Endian::Big);
     let out = t_f32.write(&json!(100.0)).unwrap();
@@ -296,6 +316,1056 @@ fn test_write_num() {
     assert!(t_f32.write(&json!(f32::MAX as f64 * 2.0)).is_err());
 }
 
+#[test]
+fn test_int128() {
+    let t_u128 = Type::uint128(Endian::Big);
+    let data = u128::MAX.to_be_bytes();
+    assert_eq!(t_u128.read(data.view_bits()).unwrap().0, json!(u128::MAX.to_string()));
+    assert_eq!(data.view_bits::<Msb0>(), t_u128.write(&json!(u128::MAX.to_string())).unwrap());
+    assert_eq!(
+        1000u128.to_be_bytes().view_bits::<Msb0>(),
+        t_u128.write(&json!(1000)).unwrap()
+    );
+    assert!(t_u128.write(&json!(-1)).is_err());
+    assert!(t_u128.write(&json!("-1")).is_err());
+    assert!(t_u128.write(&json!("abc")).is_err());
+    assert!(t_u128.write(&json!("340282366920938463463374607431768211456")).is_err());
+
+    let t_i128 = Type::int128(Endian::Little);
+    let data = i128::MIN.to_le_bytes();
+    assert_eq!(t_i128.read(data.view_bits()).unwrap().0, json!(i128::MIN.to_string()));
+    assert_eq!(data.view_bits::<Msb0>(), t_i128.write(&json!(i128::MIN.to_string())).unwrap());
+    assert_eq!(
+        (-5i128).to_le_bytes().view_bits::<Msb0>(),
+        t_i128.write(&json!(-5)).unwrap()
+    );
+
+    let t_i128 = Type::Int128 {
+        unit: Unit::new(Endian::Big, BitSize(72)),
+    };
+    let out = t_i128.write(&json!("-2")).unwrap();
+    assert_eq!(out.as_raw_slice(), [0xFF; 8].iter().chain(&[0xFE]).copied().collect::<Vec<u8>>());
+    assert_eq!(t_i128.read(&out).unwrap().0, json!("-2"));
+}
+
+#[test]
+fn test_half_float() {
+    let t_f16 = Type::float16(Endian::Big);
+    assert_eq!([0x3Cu8, 0x00].view_bits::<Msb0>(), t_f16.write(&json!(1.0)).unwrap());
+    assert_eq!(t_f16.read([0xC0u8, 0x00].view_bits()).unwrap().0, json!(-2.0));
+    let data = t_f16.write(&json!(65504.0)).unwrap();
+    assert_eq!(t_f16.read(&data).unwrap().0, json!(65504.0));
+    assert!(t_f16.write(&json!(70000.0)).is_err());
+
+    let t_f16 = Type::float16(Endian::Little);
+    assert_eq!([0x00u8, 0x3C].view_bits::<Msb0>(), t_f16.write(&json!(1.0)).unwrap());
+    assert_eq!(t_f16.read([0x00u8, 0x3C].view_bits()).unwrap().0, json!(1.0));
+
+    let t_bf16 = Type::bfloat16(Endian::Big);
+    assert_eq!([0x3Fu8, 0x80].view_bits::<Msb0>(), t_bf16.write(&json!(1.0)).unwrap());
+    assert_eq!(t_bf16.read([0x40u8, 0x49].view_bits()).unwrap().0, json!(3.140625));
+    let data = t_bf16.write(&json!(-0.5)).unwrap();
+    assert_eq!(t_bf16.read(&data).unwrap().0, json!(-0.5));
+    assert!(t_bf16.write(&json!(f64::MAX)).is_err());
+}
+
+#[test]
+fn test_fixed() {
+    let q16 = Type::fixed(true, 16, 16, Endian::Big);
+    let data = 0x0001_8000u32.to_be_bytes();
+    assert_eq!(q16.read(data.view_bits()).unwrap().0, json!(1.5));
+    assert_eq!(data.view_bits::<Msb0>(), q16.write(&json!(1.5)).unwrap());
+    let data = (-98304i32).to_be_bytes();
+    assert_eq!(q16.read(data.view_bits()).unwrap().0, json!(-1.5));
+    assert_eq!(data.view_bits::<Msb0>(), q16.write(&json!(-1.5)).unwrap());
+    assert!(q16.write(&json!(32768.0)).is_err());
+    assert!(q16.write(&json!(-32768.0)).is_ok());
+    // 64位时上限不能因转换为浮点数而进位
+    let q64 = Type::fixed(true, 64, 0, Endian::Big);
+    assert!(q64.write(&json!(9223372036854775808.0)).is_err());
+    assert!(q64.write(&json!(-9223372036854775808.0)).is_ok());
+    assert_eq!(
+        9223372036854774784i64.to_be_bytes().view_bits::<Msb0>(),
+        q64.write(&json!(9223372036854774784.0)).unwrap()
+    );
+    assert!(Type::fixed(false, 64, 0, Endian::Big).write(&json!(18446744073709551616.0)).is_err());
+    assert!(Type::fixed(true, 32, 32, Endian::Big).write(&json!(1e308)).is_err());
+
+    let q4 = Type::Fixed {
+        signed: false,
+        integer_bits: 4,
+        fraction_bits: 4,
+        unit: Unit::big_endian(),
+        rounding: Rounding::NearestEven,
+    };
+    assert_eq!(q4.read([0xA8u8].view_bits()).unwrap().0, json!(10.5));
+    assert_eq!([0x02u8].view_bits::<Msb0>(), q4.write(&json!(0.15625)).unwrap());
+    assert!(q4.write(&json!(-0.5)).is_err());
+    assert!(q4.write(&json!(16.0)).is_err());
+
+
+    let q4 = Type::Fixed {
+        signed: false,
+        integer_bits: 4,
+        fraction_bits: 4,
+        unit: Unit::big_endian(),
+        rounding: Rounding::Ceil,
+    };
+    assert_eq!([0x03u8].view_bits::<Msb0>(), q4.write(&json!(0.15625)).unwrap());
+}
+
+#[test]
+fn test_bcd() {
+    let t = Type::bcd(6, Endian::Big);
+    assert_eq!(t.read([0x20u8, 0x23, 0x12].view_bits()).unwrap().0, json!(202312));
+    assert_eq!([0x00u8, 0x01, 0x23].view_bits::<Msb0>(), t.write(&json!(123)).unwrap());
+    assert_eq!([0x00u8, 0x01, 0x23].view_bits::<Msb0>(), t.write(&json!("000123")).unwrap());
+    assert!(t.read([0x1Au8, 0x00, 0x00].view_bits()).is_err());
+    assert!(t.write(&json!("12a")).is_err());
+    assert!(t.write(&json!(1234567)).is_err());
+    assert!(t.write(&json!(-1)).is_err());
+
+    let t = Type::Bcd {
+        digits: 5,
+        unpacked: false,
+        signed: true,
+        endian: Some(Endian::Little),
+        as_string: true,
+    };
+    let data = [0x5Du8, 0x34, 0x12];
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!("-12345"));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!(-12345)).unwrap());
+    assert_eq!(t.read([0x0Cu8, 0x00, 0x00].view_bits()).unwrap().0, json!("00000"));
+
+    let t = Type::Bcd {
+        digits: 4,
+        unpacked: true,
+        signed: false,
+        endian: Some(Endian::Big),
+        as_string: true,
+    };
+    assert_eq!(t.read([0x30u8, 0x31, 0x32, 0x39].view_bits()).unwrap().0, json!("0129"));
+    assert_eq!([0u8, 1, 2, 9].view_bits::<Msb0>(), t.write(&json!("129")).unwrap());
+
+    // 位数为奇数时的填充半字节
+    let t = Type::bcd(5, Endian::Big);
+    assert_eq!(t.read([0x01u8, 0x23, 0x45].view_bits()).unwrap().0, json!(12345));
+    assert!(t.read([0xF1u8, 0x23, 0x45].view_bits()).is_err());
+    let t = Type::Bcd {
+        digits: 4,
+        unpacked: false,
+        signed: true,
+        endian: Some(Endian::Big),
+        as_string: false,
+    };
+    assert_eq!(t.read([0x01u8, 0x23, 0x4D].view_bits()).unwrap().0, json!(-1234));
+    assert_eq!(t.read([0xD1u8, 0x23, 0x4D].view_bits()).unwrap().0, json!(-1234));
+    assert!(t.read([0x51u8, 0x23, 0x4D].view_bits()).is_err());
+
+    let t = Type::bcd(20, Endian::Big);
+    let data = [0x99u8; 10];
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!("99999999999999999999"));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!("99999999999999999999")).unwrap());
+}
+
+#[test]
+fn test_string_encoding() {
+    let t = Type::string_with_encoding(BytesSize::Fixed(4), Encoding::Gbk);
+    let data = [0xC4u8, 0xE3, 0xBA, 0xC3];
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!("你好"));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!("你好")).unwrap());
+
+    let t = Type::string_with_encoding(BytesSize::Fixed(6), Encoding::Utf16Be);
+    let data = [0x00u8, 0x41, 0xD8, 0x3D, 0xDE, 0x00];
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!("A😀"));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!("A😀")).unwrap());
+
+    let t = Type::string_with_encoding(BytesSize::Fixed(4), Encoding::Utf32Le);
+    assert_eq!(t.read([0x41u8, 0, 0, 0].view_bits()).unwrap().0, json!("A"));
+    assert!(t.read([0xFFu8, 0xFF, 0xFF, 0xFF].view_bits()).is_err());
+
+    let t = Type::string_with_encoding(BytesSize::Fixed(2), Encoding::Latin1);
+    assert_eq!(t.read([0x41u8, 0xE9].view_bits()).unwrap().0, json!("Aé"));
+    assert_eq!([0x41u8, 0xE9].view_bits::<Msb0>(), t.write(&json!("Aé")).unwrap());
+
+    let t = Type::string_with_encoding(BytesSize::Fixed(2), Encoding::Ascii);
+    assert!(t.read([0x41u8, 0xE9].view_bits()).is_err());
+    assert!(t.write(&json!("Aé")).is_err());
+
+    let t = Type::String {
+        size: Some(BytesSize::Fixed(2)),
+        encoding: Encoding::Ascii,
+        lossy: true,
+        padding: None,
+    };
+    assert_eq!(t.read([0x41u8, 0xE9].view_bits()).unwrap().0, json!("A\u{FFFD}"));
+    assert_eq!([0x41u8, b'?'].view_bits::<Msb0>(), t.write(&json!("Aé")).unwrap());
+
+    let t = Type::String {
+        size: Some(BytesSize::Fixed(3)),
+        encoding: Encoding::Gbk,
+        lossy: true,
+        padding: None,
+    };
+    assert_eq!([0xC4u8, 0xE3, b'?'].view_bits::<Msb0>(), t.write(&json!("你😀")).unwrap());
+    assert!(Type::string_with_encoding(BytesSize::Fixed(2), Encoding::Gbk)
+        .write(&json!("😀"))
+        .is_err());
+
+    let t = Type::String {
+        size: Some(BytesSize::Fixed(2)),
+        encoding: Encoding::Utf8,
+        lossy: true,
+        padding: None,
+    };
+    assert_eq!(t.read([0x41u8, 0xFF].view_bits()).unwrap().0, json!("A\u{FFFD}"));
+}
+
+#[test]
+fn test_padding() {
+    let t = Type::padded_string(8, 0);
+    let data = [b'n', b'a', b'm', b'e', 0, 0, 0, 0];
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!("name"));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!("name")).unwrap());
+    assert_eq!(t.read([0u8; 8].view_bits()).unwrap().0, json!(""));
+    assert!(t.write(&json!("too long name")).is_err());
+
+    let t = Type::String {
+        size: Some(BytesSize::Fixed(6)),
+        encoding: Encoding::Utf16Le,
+        lossy: false,
+        padding: Some(0),
+    };
+    let data = [0x41u8, 0x00, 0x00, 0x01, 0x00, 0x00];
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!("A\u{100}"));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!("A\u{100}")).unwrap());
+
+    let t = Type::padded_bin(4, 0xFF);
+    assert_eq!(t.read([1u8, 2, 0xFF, 0xFF].view_bits()).unwrap().0, json!([1, 2]));
+    assert_eq!([1u8, 2, 0xFF, 0xFF].view_bits::<Msb0>(), t.write(&json!([1, 2])).unwrap());
+
+    let t = Type::new_struct(vec![
+        Field::new("name", Type::padded_string(4, b' ')),
+        Field::new("id", Type::uint8()),
+    ]);
+    let data = [b'a', b'b', b' ', b' ', 7];
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!({"name": "ab", "id": 7}));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!({"name": "ab", "id": 7})).unwrap());
+}
+
+#[test]
+fn test_mapping() {
+    let t = Type::mapping(
+        Type::uint16(Endian::Big),
+        range_map!(0 => "OK".to_string(), 3 => "TIMEOUT".to_string(), 10..20 => "RESERVED".to_string()),
+    );
+    assert_eq!(t.read([0u8, 3].view_bits()).unwrap().0, json!("TIMEOUT"));
+    assert_eq!(t.read([0u8, 15].view_bits()).unwrap().0, json!("RESERVED"));
+    assert_eq!(t.read([0u8, 5].view_bits()).unwrap().0, json!(5));
+    assert_eq!([0u8, 3].view_bits::<Msb0>(), t.write(&json!("TIMEOUT")).unwrap());
+    assert_eq!([0u8, 5].view_bits::<Msb0>(), t.write(&json!(5)).unwrap());
+    assert!(t.write(&json!("RESERVED")).is_err());
+    assert!(t.write(&json!("UNKNOWN")).is_err());
+
+    let t = Type::new_struct(vec![
+        Field::new("status", t),
+        Field::new("code", Type::int8()),
+    ]);
+    let data = [0u8, 0, 0xFF];
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!({"status": "OK", "code": -1}));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!({"status": "OK", "code": -1})).unwrap());
+}
+
+#[test]
+fn test_flags() {
+    let t = Type::flags(Unit::default(), [(0, "ready"), (1, "busy"), (7, "error")]);
+    assert_eq!(t.read([0b1000_0001u8].view_bits()).unwrap().0, json!(["ready", "error"]));
+    assert_eq!([0b1000_0001u8].view_bits::<Msb0>(), t.write(&json!(["error", "ready"])).unwrap());
+    assert_eq!([0u8].view_bits::<Msb0>(), t.write(&json!([])).unwrap());
+    assert!(t.write(&json!(["unknown"])).is_err());
+
+    let t = Type::Flags {
+        unit: Unit::new(Endian::Little, BitSize(16)),
+        flags: [(0, "ready".to_string()), (9, "overheat".to_string())].into(),
+        format: FlagsFormat::Object,
+    };
+    let data = [0x01u8, 0x02];
+    assert_eq!(
+        t.read(data.view_bits()).unwrap().0,
+        json!({"ready": true, "overheat": true})
+    );
+    assert_eq!(
+        data.view_bits::<Msb0>(),
+        t.write(&json!({"ready": true, "overheat": true})).unwrap()
+    );
+    assert_eq!(
+        [0x00u8, 0x02].view_bits::<Msb0>(),
+        t.write(&json!({"ready": false, "overheat": true})).unwrap()
+    );
+    assert_eq!([0x01u8, 0x00].view_bits::<Msb0>(), t.write(&json!(["ready"])).unwrap());
+    assert!(t.write(&json!({"ready": 1})).is_err());
+    assert!(t.write(&json!({"unknown": true})).is_err());
+}
+
+#[test]
+fn test_timestamp() {
+    let t = Type::timestamp(Type::uint32(Endian::Big), Epoch::Unix, Resolution::Seconds);
+    let data = 1_700_000_000u32.to_be_bytes();
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!("2023-11-14T22:13:20Z"));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!("2023-11-14T22:13:20Z")).unwrap());
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!("2023-11-15T06:13:20+08:00")).unwrap());
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!(1_700_000_000u32)).unwrap());
+    assert!(t.write(&json!("2023-11-14 22:13:20")).is_err());
+    assert!(t.write(&json!("1960-01-01T00:00:00Z")).is_err());
+
+    let t = Type::Timestamp {
+        original_type: Box::new(Type::uint64(Endian::Little)),
+        epoch: Epoch::Windows,
+        resolution: Resolution::HundredNanoseconds,
+        timezone: Some(chrono::FixedOffset::east_opt(8 * 3600).unwrap()),
+    };
+    let data = 133_444_736_001_234_567u64.to_le_bytes();
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!("2023-11-15T06:13:20.123456700+08:00"));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!("2023-11-14T22:13:20.1234567Z")).unwrap());
+
+    let t = Type::timestamp(Type::int64(Endian::Big), Epoch::Gps, Resolution::Milliseconds);
+    assert_eq!(t.read(0i64.to_be_bytes().view_bits()).unwrap().0, json!("1980-01-06T00:00:00Z"));
+    assert_eq!(t.read((-1i64).to_be_bytes().view_bits()).unwrap().0, json!("1980-01-05T23:59:59.999Z"));
+
+    let t = Type::timestamp(Type::bcd(8, Endian::Big), Epoch::Ntp, Resolution::Seconds);
+    assert_eq!(t.read([0u8, 0, 0, 0x60].view_bits()).unwrap().0, json!("1900-01-01T00:01:00Z"));
+    assert_eq!([0u8, 0, 0, 0x60].view_bits::<Msb0>(), t.write(&json!("1900-01-01T00:01:00Z")).unwrap());
+}
+
+#[test]
+fn test_address() {
+    let t = Type::Ipv4;
+    assert_eq!(t.read([192u8, 168, 0, 1].view_bits()).unwrap().0, json!("192.168.0.1"));
+    assert_eq!([192u8, 168, 0, 1].view_bits::<Msb0>(), t.write(&json!("192.168.0.1")).unwrap());
+    assert!(t.write(&json!("192.168.0.256")).is_err());
+
+    let t = Type::Ipv6;
+    let mut data = [0u8; 16];
+    data[..4].copy_from_slice(&[0x20, 0x01, 0x0d, 0xb8]);
+    data[15] = 1;
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!("2001:db8::1"));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!("2001:db8::1")).unwrap());
+
+    let t = Type::Mac;
+    let data = [0xAAu8, 0xBB, 0xCC, 0x01, 0x02, 0x03];
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!("aa:bb:cc:01:02:03"));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!("AA-BB-CC-01-02-03")).unwrap());
+    assert!(t.write(&json!("aa:bb:cc:01:02")).is_err());
+    assert!(t.write(&json!("aa:bb:cc:01:02:+3")).is_err());
+
+    let data = [
+        0x12u8, 0x3e, 0x45, 0x67, 0xe8, 0x9b, 0x12, 0xd3, 0xa4, 0x56, 0x42, 0x66, 0x14, 0x17, 0x40,
+        0x00,
+    ];
+    let uuid = "123e4567-e89b-12d3-a456-426614174000";
+    let t = Type::uuid(Endian::Big);
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!(uuid));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!(uuid)).unwrap());
+    assert_eq!(
+        data.view_bits::<Msb0>(),
+        t.write(&json!("{123E4567E89B12D3A456426614174000}")).unwrap()
+    );
+    assert!(t.write(&json!("123e4567-e89b12d3-a456-426614174000")).is_err());
+
+    let t = Type::uuid(Endian::Little);
+    let guid = [
+        0x67u8, 0x45, 0x3e, 0x12, 0x9b, 0xe8, 0xd3, 0x12, 0xa4, 0x56, 0x42, 0x66, 0x14, 0x17, 0x40,
+        0x00,
+    ];
+    assert_eq!(t.read(guid.view_bits()).unwrap().0, json!(uuid));
+    assert_eq!(guid.view_bits::<Msb0>(), t.write(&json!(uuid)).unwrap());
+}
+
+#[test]
+fn test_padding_field() {
+    let t = Type::new_struct(vec![
+        Field::new("a", Type::uint8()),
+        Field::new("reserved", Type::padding(BitSize(12))),
+        Field::new("b", Type::Uint8 { unit: Unit::new(Endian::Big, BitSize(4)) }),
+        Field::new(
+            "reserved2",
+            Type::Padding {
+                size: BitSize(16),
+                fill: vec![0xAA, 0x55],
+            },
+        ),
+    ]);
+    let (v, d) = t.read([1u8, 0xFF, 0xF7, 0, 0].view_bits()).unwrap();
+    assert_eq!(v, json!({"a": 1, "b": 7}));
+    assert!(d.is_empty());
+    assert_eq!(
+        [1u8, 0x00, 0x07, 0xAA, 0x55].view_bits::<Msb0>(),
+        t.write(&json!({"a": 1, "b": 7})).unwrap()
+    );
+    assert!(t.read([1u8, 0xFF, 0xF7, 0].view_bits()).is_err());
+
+    let t = Type::Padding {
+        size: BitSize(24),
+        fill: vec![1, 2],
+    };
+    assert_eq!([1u8, 2, 1].view_bits::<Msb0>(), t.write(&json!(null)).unwrap());
+}
+
+#[test]
+fn test_align() {
+    let t = Type::new_struct(vec![
+        Field::new("len", Type::uint8()),
+        Field::new("name", Type::string(BytesSize::new("len"))),
+        Field::new("align", Type::align(BitSize(32))),
+        Field::new("value", Type::uint16(Endian::Big)),
+        Field::new(
+            "align2",
+            Type::Align {
+                align: BitSize(64),
+                fill: vec![0xFF],
+            },
+        ),
+    ]);
+    let data = [3u8, b'a', b'b', b'c', 0x12, 0x34, 0xFF, 0xFF];
+    let (v, d) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"len": 3, "name": "abc", "value": 0x1234}));
+    assert!(d.is_empty());
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!({"name": "abc", "value": 0x1234})).unwrap());
+
+    let data = [1u8, b'a', 0, 0, 0x12, 0x34, 0xFF, 0xFF];
+    let (v, _) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"len": 1, "name": "a", "value": 0x1234}));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!({"name": "a", "value": 0x1234})).unwrap());
+    assert!(t.read(data[..6].view_bits()).is_err());
+}
+
+#[test]
+fn test_condition_field() {
+    let t = Type::new_struct(vec![
+        Field::new("has_ext", Type::BOOL_BIT),
+        Field::new("version", Type::Uint8 { unit: Unit::new(Endian::Big, BitSize(7)) }),
+        Field::new("ext", Type::uint16(Endian::Big)).with_condition("self.has_ext"),
+        Field::new("v2", Type::uint8()).with_condition("self.version >= 2"),
+        Field::new("end", Type::magic(&[0xFF])),
+    ]);
+
+    let data = [0x82u8, 0x12, 0x34, 0x56, 0xFF];
+    let (v, _) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"has_ext": true, "version": 2, "ext": 0x1234, "v2": 0x56, "end": [0xFF]}));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+
+    let data = [0x01u8, 0xFF];
+    let (v, _) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"has_ext": false, "version": 1, "end": [0xFF]}));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+    assert_eq!(
+        data.view_bits::<Msb0>(),
+        t.write(&json!({"has_ext": false, "version": 1, "ext": 0x1234})).unwrap()
+    );
+
+    // 条件为真但未输入值时同样跳过
+    assert_eq!(
+        [0x82u8, 0x56, 0xFF].view_bits::<Msb0>(),
+        t.write(&json!({"has_ext": true, "version": 2, "v2": 0x56})).unwrap()
+    );
+
+    let t = Type::new_struct(vec![
+        Field::new("a", Type::uint8()).with_condition("self.unknown"),
+    ]);
+    assert!(t.read([0u8].view_bits()).is_err());
+
+    // 条件引用自动计算的字段
+    let t = Type::new_struct(vec![
+        Field::new("len", Type::uint8()),
+        Field::new("data", Type::bin(BytesSize::new("len"))),
+        Field::new("ext", Type::uint8()).with_condition("self.len > 1"),
+    ]);
+    assert_eq!([2u8, 0xAA, 0xBB, 0x01].view_bits::<Msb0>(), t.write(&json!({"data": [0xAA, 0xBB], "ext": 1})).unwrap());
+    assert_eq!([1u8, 0xAA].view_bits::<Msb0>(), t.write(&json!({"data": [0xAA], "ext": 1})).unwrap());
+
+    let t = Type::new_struct(vec![
+        Field::new("len", Type::uint8()),
+        Field::new("ext", Type::uint8()).with_condition("self.len > 1"),
+        Field::new("data", Type::bin(BytesSize::new("len"))),
+    ]);
+    let data = [2u8, 0x01, 0xAA, 0xBB];
+    let v = t.read(data.view_bits()).unwrap().0;
+    assert_eq!(v, json!({"len": 2, "ext": 1, "data": [0xAA, 0xBB]}));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!({"ext": 1, "data": [0xAA, 0xBB]})).unwrap());
+}
+
+#[test]
+fn test_ref() {
+    let node = Type::new_struct(vec![
+        Field::new("value", Type::uint8()),
+        Field::new("count", Type::uint8()),
+        Field::new("children", Type::Array {
+            element_type: Box::new(Type::new_ref("node")),
+            length: Some(Length::By("count".to_string())),
+            size: None,
+        }),
+    ]);
+    let t = Type::new_struct(vec![Field::new("root", Type::new_ref("node"))])
+        .with_definition("node", node);
+
+    let data = [1u8, 2, 2, 1, 4, 0, 3, 0];
+    let (v, _) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"root": {"value": 1, "count": 2, "children": [
+        {"value": 2, "count": 1, "children": [{"value": 4, "count": 0, "children": []}]},
+        {"value": 3, "count": 0, "children": []},
+    ]}}));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+    assert_eq!(t, serde_json::from_str(&serde_json::to_string(&t).unwrap()).unwrap());
+
+    // 内层的定义覆盖外层的同名定义
+    let t = Type::new_struct(vec![
+        Field::new("a", Type::new_ref("v")),
+        Field::new("b", Type::new_struct(vec![Field::new("c", Type::new_ref("v"))])
+            .with_definition("v", Type::uint16(Endian::Big))),
+    ]).with_definition("v", Type::converter(Type::uint8(), "self + 1", "self - 1"));
+    let (v, _) = t.read([1u8, 0, 2].view_bits()).unwrap();
+    assert_eq!(v, json!({"a": 2.0, "b": {"c": 2}}));
+    assert_eq!(t.convert(&v, false).unwrap(), json!({"a": 1.0, "b": {"c": 2}}));
+
+    // 类型定义中的引用在定义所在的作用域中查找，不受引用处的内层定义影响
+    let t = Type::new_struct(vec![
+        Field::new("a", Type::new_struct(vec![Field::new("b", Type::new_ref("pair"))])
+            .with_definition("v", Type::uint16(Endian::Big))),
+    ])
+        .with_definition("v", Type::uint8())
+        .with_definition("pair", Type::new_struct(vec![
+            Field::new("x", Type::new_ref("v")),
+            Field::new("y", Type::new_ref("v")),
+        ]));
+    let data = [1u8, 2];
+    let (v, _) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"a": {"b": {"x": 1, "y": 2}}}));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+
+    assert!(Type::new_ref("v").read([0u8].view_bits()).is_err());
+    let t = Type::new_struct(vec![Field::new("a", Type::new_ref("a"))])
+        .with_definition("a", Type::new_ref("a"));
+    assert!(t.read([0u8].view_bits()).is_err());
+    assert!(t.write(&json!({"a": 0})).is_err());
+}
+
+#[test]
+fn test_tlv() {
+    let name = Type::String {
+        size: None,
+        encoding: Encoding::Utf8,
+        lossy: false,
+        padding: None,
+    };
+    let mut t = Type::tlv(
+        Type::uint8(),
+        Type::var_int(false, false),
+        range_map!(1 => Type::uint16(Endian::Big), 2 => name),
+    );
+    if let Type::Tlv { names, .. } = &mut t {
+        *names = range_map!(1 => "id".to_string(), 2 => "name".to_string());
+    }
+
+    let data = [1u8, 2, 0x12, 0x34, 2, 3, b'a', b'b', b'c', 9, 1, 0xFF];
+    let (v, _) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!([
+        {"tag": "id", "value": 0x1234},
+        {"tag": "name", "value": "abc"},
+        {"tag": 9, "value": [0xFF]},
+    ]));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+    assert_eq!(
+        data.view_bits::<Msb0>(),
+        t.write(&json!([
+            {"tag": 1, "value": 0x1234},
+            {"tag": "name", "value": "abc"},
+            {"tag": 9, "value": [0xFF]},
+        ])).unwrap()
+    );
+    assert!(t.write(&json!([{"tag": "unknown", "value": 1}])).is_err());
+    assert!(t.read([1u8, 3, 0].view_bits()).is_err());
+
+    if let Type::Tlv { format, .. } = &mut t {
+        *format = TlvFormat::Object;
+    }
+    let (v, _) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"id": 0x1234, "name": "abc", "9": [0xFF]}));
+
+    if let Type::Tlv { size, .. } = &mut t {
+        *size = Some(BytesSize::By("len".to_string()));
+    }
+
+    // 作为结构体字段时，可以通过其他字段指定大小
+    let s = Type::new_struct(vec![
+        Field::new("len", Type::uint8()),
+        Field::new("tlv", t),
+        Field::new("end", Type::magic(&[0xEE])),
+    ]);
+    let data = [4u8, 1, 2, 0x12, 0x34, 0xEE];
+    let (v, _) = s.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"len": 4, "tlv": {"id": 0x1234}, "end": [0xEE]}));
+    assert_eq!(data.view_bits::<Msb0>(), s.write(&json!({"tlv": {"id": 0x1234}})).unwrap());
+
+    // 长度过大
+    let t = Type::tlv(Type::uint8(), Type::uint64(Endian::Big), range_map! {});
+    let data = [1u8, 0x20, 0, 0, 0, 0, 0, 0, 0];
+    assert!(matches!(t.read(data.view_bits()), Err(ReadBinError::TlvError(_))));
+}
+
+#[test]
+fn test_array_until() {
+    let t = Type::Array {
+        element_type: Box::new(Type::uint8()),
+        length: Some(Length::until(Terminator::Value(json!(0)))),
+        size: None,
+    };
+    let data = [1u8, 2, 3, 0];
+    let (v, d) = t.read([1u8, 2, 3, 0, 9].view_bits()).unwrap();
+    assert_eq!(v, json!([1, 2, 3]));
+    assert_eq!(d.len(), 8);
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!([1, 2, 3, 0])).unwrap());
+    assert!(t.write(&json!([1, 0, 3])).is_err());
+    assert!(t.read([1u8, 2].view_bits()).is_err());
+
+    let record = Type::new_struct(vec![
+        Field::new("type", Type::uint8()),
+        Field::new("value", Type::uint8()),
+    ]);
+    let t = Type::new_struct(vec![
+        Field::new("records", Type::Array {
+            element_type: Box::new(record.clone()),
+            length: Some(Length::Until {
+                until: Terminator::Expr("self.type == 0".to_string()),
+                include: true,
+                consume: false,
+            }),
+            size: None,
+        }),
+        Field::new("end", Type::uint8()),
+    ]);
+    let data = [1u8, 10, 2, 20, 0, 5];
+    let (v, _) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({
+        "records": [{"type": 1, "value": 10}, {"type": 2, "value": 20}, {"type": 0, "value": 5}],
+        "end": 0,
+    }));
+
+    let t = Type::Array {
+        element_type: Box::new(record),
+        length: Some(Length::until(Terminator::Expr("self.type == 0".to_string()))),
+        size: None,
+    };
+    let (v, d) = t.read([1u8, 10, 0, 0, 3, 30].view_bits()).unwrap();
+    assert_eq!(v, json!([{"type": 1, "value": 10}]));
+    assert_eq!(d.len(), 16);
+
+    // 成员不占用数据时不会无限读取
+    let t = Type::Array {
+        element_type: Box::new(Type::new_struct(vec![])),
+        length: Some(Length::until(Terminator::Value(json!(0)))),
+        size: None,
+    };
+    assert!(matches!(t.read([1u8].view_bits()), Err(ReadBinError::TerminatorError(_))));
+}
+
+#[test]
+fn test_expr_size() {
+    let t = Type::new_struct(vec![
+        Field::new("ihl", Type::uint8()),
+        Field::new("count", Type::uint8()),
+        Field::new("options", Type::Bin {
+            size: Some(BytesSize::expr("self.ihl * 4 - 20")),
+            padding: None,
+        }),
+        Field::new("pairs", Type::Array {
+            element_type: Box::new(Type::uint8()),
+            length: Some(Length::expr("self.count * 2")),
+            size: None,
+        }),
+    ]);
+
+    let data = [6u8, 1, 1, 2, 3, 4, 0xA, 0xB];
+    let (v, _) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"ihl": 6, "count": 1, "options": [1, 2, 3, 4], "pairs": [0xA, 0xB]}));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+    // 反算被引用字段的值
+    assert_eq!(
+        data.view_bits::<Msb0>(),
+        t.write(&json!({"options": [1, 2, 3, 4], "pairs": [0xA, 0xB]})).unwrap()
+    );
+    // 大小与表达式的结果不一致
+    assert!(t.write(&json!({"ihl": 5, "count": 1, "options": [1, 2, 3, 4], "pairs": [0xA, 0xB]})).is_err());
+    // 无法反算为整数
+    assert!(t.write(&json!({"options": [1, 2, 3], "pairs": [0xA, 0xB]})).is_err());
+    assert!(t.write(&json!({"ihl": 6, "options": [1, 2, 3, 4], "pairs": [0xA]})).is_err());
+    assert!(t.read([4u8, 1].view_bits()).is_err());
+
+    // 反算的结果必须与读取时的计算结果一致
+    let with_size = |expr: &str| Type::new_struct(vec![
+        Field::new("x", Type::uint8()),
+        Field::new("data", Type::Bin { size: Some(BytesSize::expr(expr)), padding: None }),
+    ]);
+    let t = with_size("self.x * (self.x - 1) * (self.x - 2) + self.x");
+    assert!(t.write(&json!({"data": vec![0u8; 6]})).is_err());
+    let t = with_size("self.x / 4 * 4");
+    assert!(t.write(&json!({"data": vec![0u8; 6]})).is_err());
+    let t = with_size("(self.x + 3) / 4");
+    let data = t.write(&json!({"data": [1, 2]})).unwrap();
+    assert_eq!(data, [5u8, 1, 2].view_bits::<Msb0>());
+    assert_eq!(t.read(&data).unwrap().0, json!({"x": 5, "data": [1, 2]}));
+
+    // 长度为0时不读取任何成员
+    let t = Type::new_struct(vec![
+        Field::new("n", Type::uint8()),
+        Field::new("items", Type::Array {
+            element_type: Box::new(Type::uint8()),
+            length: Some(Length::expr("self.n * 2")),
+            size: None,
+        }),
+        Field::new("tail", Type::uint8()),
+    ]);
+    let data = [0u8, 7];
+    let (v, d) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"n": 0, "items": [], "tail": 7}));
+    assert!(d.is_empty());
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!({"items": [], "tail": 7})).unwrap());
+}
+
+#[test]
+fn test_pointer() {
+    let t = Type::new_struct(vec![
+        Field::new("head", Type::magic(&[0xAA])),
+        Field::new("inner", Type::new_struct(vec![
+            Field::new("local", Type::pointer(Type::uint8(), Type::uint16(Endian::Big), PointerBase::Struct)),
+            Field::new("global", Type::pointer(Type::uint8(), Type::uint8(), PointerBase::Buffer)),
+        ])),
+        Field::new("tail", Type::uint8()),
+    ]);
+
+    let data = [0xAAu8, 2, 6, 0x12, 0x34, 0x55, 0x77];
+    let (v, d) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"head": [0xAA], "inner": {"local": 0x1234, "global": 0x77}, "tail": 0x55}));
+    assert_eq!(d.len(), 8);
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+
+    let t = Type::pointer(Type::uint8(), Type::uint8(), PointerBase::Buffer);
+    assert!(t.read([5u8, 0].view_bits()).is_err());
+    let t = Type::pointer(Type::var_int(false, false), Type::Bin { size: None, padding: None }, PointerBase::Buffer);
+    assert_eq!([1u8, 0xFF].view_bits::<Msb0>(), t.write(&json!([0xFF])).unwrap());
+    // 偏移量的大小必须固定
+    let t = Type::new_struct(vec![
+        Field::new("data", Type::Bin { size: None, padding: None }),
+        Field::new("p", t),
+    ]);
+    assert!(t.write(&json!({"data": vec![0u8; 200], "p": [1]})).is_err());
+
+    // 指定了大小的结构体只消耗自身的数据
+    let t = Type::new_struct_with_size(vec![Field::new("a", Type::uint8())], BytesSize::Fixed(1));
+    let (v, d) = t.read([1u8, 2, 3].view_bits()).unwrap();
+    assert_eq!(v, json!({"a": 1}));
+    assert_eq!(d.len(), 16);
+    let t = Type::new_array(t);
+    let (v, d) = t.read([1u8, 2, 3].view_bits()).unwrap();
+    assert_eq!(v, json!([{"a": 1}, {"a": 2}, {"a": 3}]));
+    assert!(d.is_empty());
+
+    // 多层嵌套的结构体中均存在指针
+    let mut t = Type::new_struct(vec![]);
+    let mut v = json!({});
+    for i in 0..32 {
+        t = Type::new_struct(vec![
+            Field::new("p", Type::pointer(Type::uint8(), Type::uint8(), PointerBase::Struct)),
+            Field::new("inner", t),
+        ]);
+        v = json!({"p": i, "inner": v});
+    }
+    let data = t.write(&v).unwrap();
+    assert_eq!(data.len(), 32 * 2 * 8);
+    assert_eq!(data[..16], [63u8, 61].view_bits::<Msb0>());
+    assert_eq!(t.read(&data).unwrap().0, v);
+}
+
+#[test]
+fn test_bit_order() {
+    let t = Type::new_struct(vec![
+        Field::new("a", Type::Uint8 { unit: Unit::new(Endian::Big, BitSize(3)) }),
+        Field::new("b", Type::BOOL_BIT),
+        Field::new("c", Type::Uint8 { unit: Unit::new(Endian::Big, BitSize(4)) }),
+        Field::new("d", Type::uint16(Endian::Little)),
+        Field::new("e", Type::Uint16 { unit: Unit::new(Endian::Little, BitSize(12)) }),
+        Field::new("f", Type::Uint8 { unit: Unit::new(Endian::Big, BitSize(4)) }),
+    ])
+    .with_bit_order(BitOrder::Lsb0);
+
+    let data = [0xADu8, 0x34, 0x12, 0xBC, 0x7A];
+    let (v, d) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"a": 5, "b": true, "c": 0xA, "d": 0x1234, "e": 0xABC, "f": 7}));
+    assert!(d.is_empty());
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+
+    // 不足整字节时按整字节计算
+    let t = Type::new_struct(vec![
+        Field::new("flags", Type::new_struct(vec![
+            Field::new("ready", Type::BOOL_BIT),
+            Field::new("error", Type::BOOL_BIT),
+        ]).with_bit_order(BitOrder::Lsb0)),
+        Field::new("len", Type::uint8()),
+    ]);
+    let data = [0b10u8, 0x05];
+    let v = json!({"flags": {"ready": false, "error": true}, "len": 5});
+    assert_eq!(t.read(data.view_bits()).unwrap().0, v);
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+
+    // 自动计算的长度字段
+    let t = Type::new_struct(vec![
+        Field::new("len", Type::uint8()),
+        Field::new("data", Type::Bin { size: Some(BytesSize::new("len")), padding: None }),
+    ])
+    .with_bit_order(BitOrder::Lsb0);
+    let data = [0x02u8, 0xAA, 0xBB];
+    let v = json!({"len": 2, "data": [0xAA, 0xBB]});
+    assert_eq!(t.read(data.view_bits()).unwrap().0, v);
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!({"data": [0xAA, 0xBB]})).unwrap());
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+
+    let t = Type::new_struct(vec![
+        Field::new("n", Type::Uint8 { unit: Unit::new(Endian::Big, BitSize(4)) }),
+        Field::new("flag", Type::Uint8 { unit: Unit::new(Endian::Big, BitSize(4)) }),
+        Field::new("items", Type::Array {
+            element_type: Box::new(Type::uint8()),
+            length: Some(Length::By("n".to_string())),
+            size: None,
+        }),
+    ])
+    .with_bit_order(BitOrder::Lsb0);
+    let data = [0x93u8, 0x01, 0x02, 0x03];
+    let v = json!({"n": 3, "flag": 9, "items": [1, 2, 3]});
+    assert_eq!(t.read(data.view_bits()).unwrap().0, v);
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!({"flag": 9, "items": [1, 2, 3]})).unwrap());
+}
+
+#[test]
+fn test_default_endian() {
+    let t = Type::new_struct(vec![
+        Field::new("a", Type::Uint16 { unit: Unit::default() }),
+        Field::new("b", Type::uint16(Endian::Big)),
+        Field::new("inner", Type::new_struct(vec![
+            Field::new("c", Type::Uint16 { unit: Unit::default() }),
+        ]).with_default_endian(Endian::Big)),
+        Field::new("d", Type::new_struct(vec![
+            Field::new("e", Type::Uint16 { unit: Unit::default() }),
+        ])),
+    ])
+    .with_default_endian(Endian::Little);
+
+    let data = [0x34u8, 0x12, 0x12, 0x34, 0x12, 0x34, 0x34, 0x12];
+    let v = json!({"a": 0x1234, "b": 0x1234, "inner": {"c": 0x1234}, "d": {"e": 0x1234}});
+    assert_eq!(t.read(data.view_bits()).unwrap().0, v);
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+
+    // 调用时指定的选项优先于结构体中的默认设置
+    let options = Options { endian: Some(Endian::Big), ..Default::default() };
+    let v = json!({"a": 0x3412, "b": 0x1234, "inner": {"c": 0x1234}, "d": {"e": 0x3412}});
+    assert_eq!(t.read_with(data.view_bits(), &options).unwrap().0, v);
+    assert_eq!(data.view_bits::<Msb0>(), t.write_with(&v, &options).unwrap());
+
+    let t = Type::new_struct(vec![
+        Field::new("x", Type::Uint8 { unit: Unit { endian: None, size: Some(BitSize(4)) } }),
+        Field::new("y", Type::Uint8 { unit: Unit { endian: None, size: Some(BitSize(4)) } }),
+    ]);
+    let options = Options { bit_order: Some(BitOrder::Lsb0), ..Default::default() };
+    assert_eq!(t.read([0x21u8].view_bits()).unwrap().0, json!({"x": 2, "y": 1}));
+    assert_eq!(t.read_with([0x21u8].view_bits(), &options).unwrap().0, json!({"x": 1, "y": 2}));
+    assert_eq!([0x21u8].view_bits::<Msb0>(), t.write_with(&json!({"x": 1, "y": 2}), &options).unwrap());
+
+    // 浮点数与BCD码同样使用默认字节顺序
+    let bcd = Type::Bcd { digits: 4, unpacked: false, signed: false, endian: None, as_string: false };
+    let t = Type::new_struct(vec![
+        Field::new("f", Type::Float32 { endian: None }),
+        Field::new("h", Type::Float16 { endian: None }),
+        Field::new("n", bcd),
+        Field::new("m", Type::bcd(4, Endian::Big)),
+    ])
+    .with_default_endian(Endian::Little);
+    let data = [0x00u8, 0x00, 0xC0, 0x3F, 0x00, 0x3C, 0x34, 0x12, 0x12, 0x34];
+    let v = json!({"f": 1.5, "h": 1.0, "n": 1234, "m": 1234});
+    assert_eq!(t.read(data.view_bits()).unwrap().0, v);
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+    let options = Options { endian: Some(Endian::Big), ..Default::default() };
+    let data = [0x3Fu8, 0xC0, 0x00, 0x00, 0x3C, 0x00, 0x12, 0x34, 0x12, 0x34];
+    assert_eq!(t.read_with(data.view_bits(), &options).unwrap().0, v);
+}
+
+#[test]
+fn test_computed() {
+    let t = Type::new_struct(vec![
+        Field::new("hi", Type::uint8()),
+        Field::new("lo", Type::uint8()),
+        Field::new("value", Type::computed("self.hi * 256 + self.lo")),
+        Field::new("voltage_raw", Type::uint16(Endian::Big)),
+        Field::new("voltage", Type::computed("self.voltage_raw * 0.01")),
+    ]);
+
+    let data = [0x12u8, 0x34, 0x01, 0xF4];
+    let (v, d) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"hi": 0x12, "lo": 0x34, "value": 4660, "voltage_raw": 500, "voltage": 5.0}));
+    assert!(v["value"].is_u64());
+    assert!(d.is_empty());
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+    // 写入时忽略计算字段
+    let v = json!({"hi": 0x12, "lo": 0x34, "voltage_raw": 500});
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+
+    let t = Type::new_struct(vec![
+        Field::new("raw", Type::uint8()),
+        Field::new("half", Type::computed("self.raw / 2")),
+        Field::new("exact", Type::computed("self.raw * 1.0 / 2")),
+    ]);
+    assert_eq!(t.read([5u8].view_bits()).unwrap().0, json!({"raw": 5, "half": 2, "exact": 2.5}));
+}
+
+#[test]
+fn test_assert() {
+    let t = Type::new_struct(vec![
+        Field::new("start_flag", Type::uint8()),
+        Field::new("total_len", Type::uint8()),
+        Field::new("payload_len", Type::uint8()).with_assert("self.payload_len <= self.total_len - 8"),
+        Field::new("payload", Type::bin(BytesSize::new("payload_len"))),
+        Field::new("end_flag", Type::uint8()).with_assert("self.end_flag == self.start_flag"),
+    ]);
+
+    let data = [0x7Eu8, 10, 2, 0xAA, 0xBB, 0x7E];
+    let v = t.read(data.view_bits()).unwrap().0;
+    assert_eq!(v, json!({"start_flag": 0x7E, "total_len": 10, "payload_len": 2, "payload": [0xAA, 0xBB], "end_flag": 0x7E}));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+
+    assert_eq!(
+        t.read([0x7Eu8, 10, 2, 0xAA, 0xBB, 0x7F].view_bits()),
+        Err(ReadBinError::AssertionFailed {
+            field: "end_flag".to_string(),
+            expr: "self.end_flag == self.start_flag".to_string(),
+        })
+    );
+    assert!(matches!(
+        t.read([0x7Eu8, 10, 3, 0xAA, 0xBB, 0xCC, 0x7E].view_bits()),
+        Err(ReadBinError::AssertionFailed { field, .. }) if field == "payload_len"
+    ));
+
+    // 自动计算的字段使用计算得到的值检查
+    let v = json!({"start_flag": 0x7E, "total_len": 10, "payload": [0xAA, 0xBB], "end_flag": 0x7E});
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&v).unwrap());
+    let v = json!({"start_flag": 0x7E, "total_len": 10, "payload": [1, 2, 3, 4, 5], "end_flag": 0x7E});
+    assert!(matches!(t.write(&v), Err(WriteBinError::AssertionFailed { field, .. }) if field == "payload_len"));
+    let v = json!({"start_flag": 0x7E, "total_len": 10, "payload": [0xAA, 0xBB], "end_flag": 0x7F});
+    assert!(matches!(t.write(&v), Err(WriteBinError::AssertionFailed { field, .. }) if field == "end_flag"));
+}
+
+#[test]
+fn test_compressed() {
+    for algorithm in [
+        Compression::Deflate,
+        Compression::Zlib,
+        Compression::Gzip,
+        Compression::Lz4,
+        Compression::Zstd,
+    ] {
+        let mut compressed = Type::compressed(
+            Type::new_struct(vec![
+                Field::new("id", Type::uint16(Endian::Big)),
+                Field::new("log", Type::String {
+                    size: None,
+                    encoding: Encoding::Utf8,
+                    lossy: false,
+                    padding: None,
+                }),
+            ]),
+            algorithm,
+        );
+        if let Type::Compressed { size, .. } = &mut compressed {
+            *size = Some(BytesSize::new("len"));
+        }
+        let t = Type::new_struct(vec![
+            Field::new("len", Type::uint16(Endian::Big)),
+            Field::new("payload", compressed),
+            Field::new("end", Type::uint8()),
+        ]);
+
+        let v = json!({"payload": {"id": 7, "log": "boot ok; boot ok; boot ok"}, "end": 0x7E});
+        let data = t.write(&v).unwrap();
+        let (read, rest) = t.read(&data).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(read["payload"], v["payload"]);
+        assert_eq!(read["end"], json!(0x7E));
+        assert_eq!(read["len"].as_u64().unwrap() as usize, data.len() / 8 - 3);
+
+        // 压缩数据损坏
+        let mut broken = data.into_vec();
+        broken[2] = 0xFF;
+        assert!(matches!(t.read(broken.view_bits()), Err(ReadBinError::DecompressError(_))));
+    }
+
+    // 解压后的数据过大
+    let t = Type::compressed(Type::Bin { size: None, padding: None }, Compression::Gzip);
+    let data = t.write(&json!(vec![0u8; 64 * 1024])).unwrap();
+    assert_eq!(t.read(&data).unwrap().0.as_array().unwrap().len(), 64 * 1024);
+    let options = Options { max_decompressed_size: Some(1024), ..Default::default() };
+    assert!(matches!(t.read_with(&data, &options), Err(ReadBinError::DecompressError(_))));
+}
+
+#[test]
+fn test_var_int() {
+    let t = Type::var_int(false, false);
+    assert_eq!(t.read([0xAC, 0x02].view_bits()).unwrap().0, json!(300));
+    assert_eq!([0xACu8, 0x02].view_bits::<Msb0>(), t.write(&json!(300)).unwrap());
+    assert_eq!([0u8].view_bits::<Msb0>(), t.write(&json!(0)).unwrap());
+    let max = t.write(&json!(u64::MAX)).unwrap();
+    assert_eq!(t.read(&max).unwrap().0, json!(u64::MAX));
+    assert!(t.write(&json!(-1)).is_err());
+    assert!(t.read([0xFFu8; 11].view_bits()).is_err());
+    assert!(t.read([0x80u8].view_bits()).is_err());
+
+    let t = Type::var_int(true, false);
+    let data = [0xC0u8, 0xBB, 0x78];
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!(-123456));
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!(-123456)).unwrap());
+    let min = t.write(&json!(i64::MIN)).unwrap();
+    assert_eq!(t.read(&min).unwrap().0, json!(i64::MIN));
+    let max = t.write(&json!(i64::MAX)).unwrap();
+    assert_eq!(t.read(&max).unwrap().0, json!(i64::MAX));
+    // 第10个字节中除最高位外的其余位必须为符号扩展
+    let mut data = [0xFFu8; 10];
+    data[9] = 0x01;
+    assert!(matches!(t.read(data.view_bits()), Err(ReadBinError::VarIntOverflow)));
+    data[9] = 0x7F;
+    assert_eq!(t.read(data.view_bits()).unwrap().0, json!(-1));
+
+    let t = Type::var_int(true, true);
+    assert_eq!([1u8].view_bits::<Msb0>(), t.write(&json!(-1)).unwrap());
+    assert_eq!([4u8].view_bits::<Msb0>(), t.write(&json!(2)).unwrap());
+    assert_eq!(t.read([3u8].view_bits()).unwrap().0, json!(-2));
+
+    let msg = Type::new_struct(vec![
+        Field::new("len", Type::var_int(false, false)),
+        Field::new("count", Type::var_int(false, false)),
+        Field::new(
+            "list",
+            Type::Array {
+                element_type: Box::new(Type::uint8()),
+                length: Some(Length::by_field("count")),
+                size: Some(BytesSize::new("len")),
+            },
+        ),
+    ]);
+    let list: Vec<u8> = (0..200).map(|i| i as u8).collect();
+    let out = msg.write(&json!({ "list": list })).unwrap();
+    assert_eq!(&out.as_raw_slice()[..4], &[0xC8, 0x01, 0xC8, 0x01]);
+    assert_eq!(
+        msg.read(&out).unwrap().0,
+        json!({ "len": 200, "count": 200, "list": list })
+    );
+}
+
 #[test]
 fn test_write_magic() {
     let magic = Type::magic(&[1, 2, 3]);
@@ -354,6 +1424,18 @@ fn test_read_array() {
         *length = Some(Length::Fixed(4));
     }
     assert!(array.read(b"333322211".view_bits()).is_err());
+
+    // 引用的字段为0时数组为空，不再读取之后的全部数据
+    let t = Type::new_struct(vec![
+        Field::new("count", Type::uint8()),
+        Field::new("items", Type::new_array_with_length(Type::uint8(), Length::By("count".to_string()))),
+        Field::new("tail", Type::uint8()),
+    ]);
+    let data = [0u8, 7];
+    let (v, d) = t.read(data.view_bits()).unwrap();
+    assert_eq!(v, json!({"count": 0, "items": [], "tail": 7}));
+    assert!(d.is_empty());
+    assert_eq!(data.view_bits::<Msb0>(), t.write(&json!({"items": [], "tail": 7})).unwrap());
 }
 
 #[test]
@@ -0,0 +1,149 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+use deku::ctx::Limit;
+use deku::{DekuRead, DekuWrite};
+
+use crate::error::{ReadBinError, WriteBinError};
+use crate::ty::Endian;
+use crate::Value;
+
+/// 网络地址或标识符的种类
+#[derive(Debug, Copy, Clone)]
+pub enum Address {
+    Ipv4,
+    Ipv6,
+    Mac,
+    /// UUID，参数为字节顺序
+    Uuid(Endian),
+}
+
+impl Address {
+    /// 占用的字节数
+    const fn bytes(&self) -> usize {
+        match self {
+            Address::Ipv4 => 4,
+            Address::Mac => 6,
+            Address::Ipv6 | Address::Uuid(_) => 16,
+        }
+    }
+
+    const fn name(&self) -> &'static str {
+        match self {
+            Address::Ipv4 => "IPv4",
+            Address::Ipv6 => "IPv6",
+            Address::Mac => "MAC",
+            Address::Uuid(_) => "UUID",
+        }
+    }
+}
+
+/// 将UUID在大端序与Microsoft GUID的字节顺序（前三组为小端序）之间转换
+fn swap_guid(bytes: &mut [u8]) {
+    bytes[0..4].reverse();
+    bytes[4..6].reverse();
+    bytes[6..8].reverse();
+}
+
+pub fn read_address(
+    data: &BitSlice<u8, Msb0>,
+    kind: Address,
+) -> Result<(Value, &BitSlice<u8, Msb0>), ReadBinError> {
+    let (data, mut bytes) = Vec::<u8>::read(data, Limit::new_count(kind.bytes()))?;
+
+    let s = match kind {
+        Address::Ipv4 => Ipv4Addr::from(<[u8; 4]>::try_from(bytes).unwrap()).to_string(),
+        Address::Ipv6 => Ipv6Addr::from(<[u8; 16]>::try_from(bytes).unwrap()).to_string(),
+        Address::Mac => bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(":"),
+        Address::Uuid(endian) => {
+            if endian == Endian::Little {
+                swap_guid(&mut bytes);
+            }
+            let hex = bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+            format!(
+                "{}-{}-{}-{}-{}",
+                &hex[0..8],
+                &hex[8..12],
+                &hex[12..16],
+                &hex[16..20],
+                &hex[20..]
+            )
+        }
+    };
+    Ok((s.into(), data))
+}
+
+pub fn write_address(
+    value: &Value,
+    kind: Address,
+    output: &mut BitVec<u8, Msb0>,
+) -> Result<(), WriteBinError> {
+    let s = value
+        .as_str()
+        .ok_or(WriteBinError::TypeError(kind.name()))?
+        .trim();
+    let error = || WriteBinError::FormatError(kind.name(), s.to_string());
+
+    let bytes = match kind {
+        Address::Ipv4 => s
+            .parse::<Ipv4Addr>()
+            .map_err(|_| error())?
+            .octets()
+            .to_vec(),
+        Address::Ipv6 => s
+            .parse::<Ipv6Addr>()
+            .map_err(|_| error())?
+            .octets()
+            .to_vec(),
+        Address::Mac => {
+            let sep = if s.contains('-') { '-' } else { ':' };
+            let bytes = s
+                .split(sep)
+                .map(|b| {
+                    if b.len() == 2 && b.bytes().all(|c| c.is_ascii_hexdigit()) {
+                        u8::from_str_radix(b, 16).map_err(|_| error())
+                    } else {
+                        Err(error())
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if bytes.len() != 6 {
+                return Err(error());
+            }
+            bytes
+        }
+        Address::Uuid(endian) => {
+            let uuid = s
+                .strip_prefix('{')
+                .and_then(|s| s.strip_suffix('}'))
+                .unwrap_or(s);
+            let hyphenated = uuid.len() == 36
+                && uuid
+                    .char_indices()
+                    .all(|(i, c)| matches!(i, 8 | 13 | 18 | 23) == (c == '-'));
+            if !hyphenated && uuid.len() != 32 {
+                return Err(error());
+            }
+
+            let hex = uuid.replace('-', "");
+            if hex.len() != 32 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+                return Err(error());
+            }
+            let mut bytes = (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                .collect::<Vec<_>>();
+            if endian == Endian::Little {
+                swap_guid(&mut bytes);
+            }
+            bytes
+        }
+    };
+
+    bytes.write(output, ())?;
+    Ok(())
+}
@@ -1,14 +1,28 @@
+use serde_json::Value;
+
+use crate::ty::utils::set_int_ctx;
+
 /// 数组长度
 ///
 /// **示例：**
 /// ```rust
-/// use bin2json::ty::Length;
+/// use bin2json::ty::{Length, Terminator};
 ///
 /// let length: Length = serde_json::from_str(r#"100"#)?;
 /// assert_eq!(Length::Fixed(100), length);
 ///
 /// let length: Length = serde_json::from_str(r#""field_name""#)?;
 /// assert_eq!(Length::By("field_name".to_string()), length);
+///
+/// let length: Length = serde_json::from_str(r#"{ "expr": "self.count * 2" }"#)?;
+/// assert_eq!(Length::expr("self.count * 2"), length);
+///
+/// let length: Length = serde_json::from_str(r#"{ "until": { "expr": "self.type == 0" }, "include": true }"#)?;
+/// assert_eq!(Length::Until {
+///     until: Terminator::Expr("self.type == 0".to_string()),
+///     include: true,
+///     consume: true,
+/// }, length);
 /// # Ok::<_, serde_json::Error>(())
 /// ```
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -16,12 +30,68 @@
 pub enum Length {
     /// 固定长度
     Fixed(usize),
-    /// 通过指定字段指定。*使用该枚举时数据的定义应包含在结构体中，且指定的字段顺序应在数组之前*
+    /// 通过指定字段指定。*使用该枚举时数据的定义应包含在结构体中，且指定的字段顺序应在数组之前*。字段值为0时数组为空
     By(String),
+    /// 通过表达式计算长度，规则与[`BytesSize::Expr`](crate::ty::BytesSize::Expr)相同
+    Expr {
+        /// 表达式
+        expr: String,
+    },
+    /// 读取到满足终止条件的成员为止。写入时如果最后一个成员不满足终止条件，则自动添加终止成员
+    Until {
+        /// 终止条件
+        until: Terminator,
+        /// 读取结果是否包含终止成员
+        #[serde(default)]
+        include: bool,
+        /// 是否消耗终止成员的数据。为`false`时终止成员的数据留给之后的字段读取，写入时也不会写入终止成员
+        #[serde(default = "default_consume")]
+        consume: bool,
+    },
 }
 
 impl Length {
     pub fn by_field<S: Into<String>>(field: S) -> Self {
         Self::By(field.into())
     }
+
+    pub fn expr<S: Into<String>>(expr: S) -> Self {
+        Self::Expr { expr: expr.into() }
+    }
+
+    pub fn until(until: Terminator) -> Self {
+        Self::Until {
+            until,
+            include: false,
+            consume: true,
+        }
+    }
+}
+
+fn default_consume() -> bool {
+    true
+}
+
+/// 数组的终止条件
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Terminator {
+    /// 成员的读取结果等于指定值
+    Value(Value),
+    /// 以成员的值作为变量`self`执行表达式，结果为`true`时终止。*整数为整型变量，其他数值为浮点数*
+    Expr(String),
+}
+
+impl Terminator {
+    /// 判断成员是否满足终止条件
+    pub fn matches(&self, element: &Value) -> evalexpr::EvalexprResult<bool> {
+        match self {
+            Terminator::Value(v) => Ok(v == element),
+            Terminator::Expr(expr) => {
+                let mut ctx = evalexpr::HashMapContext::new();
+                set_int_ctx(element, None, &mut ctx)?;
+                evalexpr::eval_boolean_with_context(expr, &ctx)
+            }
+        }
+    }
 }
@@ -0,0 +1,130 @@
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+use deku::ctx::Limit;
+use deku::{DekuRead, DekuWrite};
+
+use crate::error::{ReadBinError, WriteBinError};
+use crate::ty::Endian;
+use crate::Value;
+
+/// 正数的符号半字节
+const SIGN_POSITIVE: u8 = 0xC;
+/// 负数的符号半字节
+const SIGN_NEGATIVE: u8 = 0xD;
+
+/// BCD码占用的字节数
+fn bcd_bytes(digits: usize, unpacked: bool, signed: bool) -> usize {
+    let count = digits + signed as usize;
+    if unpacked {
+        count
+    } else {
+        count.div_ceil(2)
+    }
+}
+
+pub fn read_bcd(
+    data: &BitSlice<u8, Msb0>,
+    digits: usize,
+    unpacked: bool,
+    signed: bool,
+    endian: Endian,
+    as_string: bool,
+) -> Result<(Value, &BitSlice<u8, Msb0>), ReadBinError> {
+    let (data, mut bytes) = Vec::<u8>::read(
+        data,
+        Limit::new_count(bcd_bytes(digits, unpacked, signed)),
+    )?;
+    if endian == Endian::Little {
+        bytes.reverse();
+    }
+
+    let mut nibbles: Vec<u8> = if unpacked {
+        bytes.iter().map(|b| b & 0x0F).collect()
+    } else {
+        bytes.iter().flat_map(|b| [b >> 4, b & 0x0F]).collect()
+    };
+
+    let sign = if signed { nibbles.pop() } else { None };
+    let negative = match sign {
+        None => false,
+        Some(0xA | 0xC | 0xE | 0xF) => false,
+        Some(0xB | SIGN_NEGATIVE) => true,
+        Some(_) => return Err(ReadBinError::BcdError(bytes)),
+    };
+    // 压缩BCD码半字节数为奇数时最前面的填充半字节，只能为0或者符号半字节
+    let (pad, nibbles) = nibbles.split_at(nibbles.len() - digits);
+    if pad.iter().any(|n| *n != 0 && Some(*n) != sign) {
+        return Err(ReadBinError::BcdError(bytes));
+    }
+
+    let mut s = String::with_capacity(digits + 1);
+    if negative {
+        s.push('-');
+    }
+    for n in nibbles {
+        if *n > 9 {
+            return Err(ReadBinError::BcdError(bytes));
+        }
+        s.push((b'0' + n) as char);
+    }
+
+    let value = if as_string {
+        s.into()
+    } else if let Ok(v) = s.parse::<u64>() {
+        v.into()
+    } else if let Ok(v) = s.parse::<i64>() {
+        v.into()
+    } else {
+        s.into()
+    };
+    Ok((value, data))
+}
+
+pub fn write_bcd(
+    value: &Value,
+    digits: usize,
+    unpacked: bool,
+    signed: bool,
+    endian: Endian,
+    output: &mut BitVec<u8, Msb0>,
+) -> Result<(), WriteBinError> {
+    let s = match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) if n.is_u64() || n.is_i64() => n.to_string(),
+        _ => return Err(WriteBinError::TypeError("Bcd")),
+    };
+
+    let (negative, num) = if let Some(num) = s.strip_prefix('-') {
+        (true, num)
+    } else {
+        (false, s.strip_prefix('+').unwrap_or(&s))
+    };
+    if num.is_empty() || !num.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(WriteBinError::BcdError(s));
+    }
+    if num.len() > digits || (negative && !signed) {
+        return Err(WriteBinError::ValueOverflowOf("Bcd"));
+    }
+
+    let mut nibbles = Vec::with_capacity(digits + 2);
+    if !unpacked && bcd_bytes(digits, unpacked, signed) * 2 > digits + signed as usize {
+        // 压缩BCD码的半字节数为奇数时在最前面补0
+        nibbles.push(0);
+    }
+    nibbles.resize(nibbles.len() + digits - num.len(), 0);
+    nibbles.extend(num.bytes().map(|c| c - b'0'));
+    if signed {
+        nibbles.push(if negative { SIGN_NEGATIVE } else { SIGN_POSITIVE });
+    }
+
+    let mut bytes: Vec<u8> = if unpacked {
+        nibbles
+    } else {
+        nibbles.chunks(2).map(|c| c[0] << 4 | c[1]).collect()
+    };
+    if endian == Endian::Little {
+        bytes.reverse();
+    }
+
+    bytes.write(output, ())?;
+    Ok(())
+}
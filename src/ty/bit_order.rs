@@ -0,0 +1,106 @@
+use std::ops::Range;
+
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+
+use crate::ty::{Context, Endian, Unit};
+use crate::Type;
+
+/// 结构体中按位排列的数据在字节内的顺序
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub enum BitOrder {
+    /// 从字节的最高位开始排列
+    #[default]
+    Msb0,
+    /// 从字节的最低位开始排列，如小端序平台上C语言的位域、CAN总线中Intel格式的信号
+    Lsb0,
+}
+
+/// 按[`BitOrder::Lsb0`]排列时占用的比特数，以及实际读写时使用的类型
+///
+/// 按位排列的数据会先按字节内从低到高的顺序取出，再整体反转为从高到低的顺序读写，
+/// 因此整字节大小的数据需要交换字节顺序才能与[`BitOrder::Msb0`]时的结果一致，不足整字节时按小端序处理
+pub(crate) fn packed_type(ty: &Type, ctx: &Context) -> Option<(usize, Type)> {
+    let packed_unit = |unit: &Unit, bits: usize| -> (usize, Unit) {
+        let bits = unit.size.map_or(bits, |s| s.0);
+        let endian = match (bits % 8, ctx.endian(unit)) {
+            (0, Endian::Big) => Endian::Little,
+            _ => Endian::Big,
+        };
+        (bits, Unit { endian: Some(endian), ..*unit })
+    };
+
+    macro_rules! packed_int {
+        ($($variant:ident => $bits:expr),* $(,)?) => {
+            match ty {
+                $(Type::$variant { unit } => {
+                    let (bits, unit) = packed_unit(unit, $bits);
+                    return Some((bits, Type::$variant { unit }));
+                })*
+                _ => {}
+            }
+        };
+    }
+    packed_int! {
+        Int8 => 8, Int16 => 16, Int32 => 32, Int64 => 64, Int128 => 128,
+        Uint8 => 8, Uint16 => 16, Uint32 => 32, Uint64 => 64, Uint128 => 128,
+    }
+
+    match ty {
+        Type::Boolean { bit } => Some((if *bit { 1 } else { 8 }, ty.clone())),
+        Type::Flags {
+            unit,
+            flags,
+            format,
+        } => {
+            let (bits, unit) = packed_unit(unit, 8);
+            Some((
+                bits,
+                Type::Flags {
+                    unit,
+                    flags: flags.clone(),
+                    format: *format,
+                },
+            ))
+        }
+        Type::Mapping { original_type, map } => {
+            let (bits, original_type) = packed_type(original_type, ctx)?;
+            Some((
+                bits,
+                Type::Mapping {
+                    original_type: Box::new(original_type),
+                    map: map.clone(),
+                },
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// 字节内从低位开始的第`pos`个比特位在从高位开始排列时的位置
+fn lsb0_index(pos: usize) -> usize {
+    pos - pos % 8 + 7 - pos % 8
+}
+
+/// 从`data`的`offset`处按[`BitOrder::Lsb0`]取出`bits`个比特位，结果为从高到低的顺序。数据不足时返回[`None`]
+pub(crate) fn read_lsb0(
+    data: &BitSlice<u8, Msb0>,
+    offset: usize,
+    bits: usize,
+) -> Option<BitVec<u8, Msb0>> {
+    (offset..offset + bits)
+        .rev()
+        .map(|pos| data.get(lsb0_index(pos)).map(|b| *b))
+        .collect()
+}
+
+/// 将`data`中被`ranges`覆盖的字节转换为[`BitOrder::Lsb0`]的排列。`data`必须为整字节
+pub(crate) fn to_lsb0(data: &mut BitVec<u8, Msb0>, ranges: &[Range<usize>]) {
+    let mut bytes = ranges
+        .iter()
+        .flat_map(|r| r.start / 8..r.end.div_ceil(8))
+        .collect::<Vec<_>>();
+    bytes.dedup();
+    for byte in bytes {
+        data[byte * 8..byte * 8 + 8].reverse();
+    }
+}
@@ -28,6 +28,9 @@ use crate::range::KeyRangeMap;
 ///     map: bin2json::range_map!(1 => 2, 3.. => 4)
 /// });
 ///
+/// let bs: BytesSize = serde_json::from_str(r#"{ "expr": "self.header_len * 4 - 20" }"#).unwrap();
+/// assert_eq!(bs, BytesSize::expr("self.header_len * 4 - 20"));
+///
 /// ```
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -45,6 +48,14 @@ pub enum BytesSize {
         /// 键为指定字段的值，值为大小
         map: KeyRangeMap<usize>,
     },
+    /// 通过表达式计算大小，表达式中可以使用`self.field_name`引用之前的同级字段
+    ///
+    /// 写入时，如果表达式只引用了一个未输入值的字段，且为线性表达式（如：`self.len * 4 - 20`），则根据写入数据的大小反算该字段的值；
+    /// 否则检查写入数据的大小是否与表达式的结果一致
+    Expr {
+        /// 表达式
+        expr: String,
+    },
 }
 
 impl BytesSize {
@@ -55,6 +66,10 @@ impl BytesSize {
         }
     }
 
+    pub fn expr<S: Into<String>>(expr: S) -> Self {
+        Self::Expr { expr: expr.into() }
+    }
+
     pub fn new<BS: Into<BytesSize>>(bs: BS) -> Self {
         bs.into()
     }
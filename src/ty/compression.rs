@@ -0,0 +1,109 @@
+use std::io::{Read, Write};
+
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+use deku::ctx::{BitSize, Limit};
+use deku::DekuRead;
+
+use crate::error::{ReadBinError, WriteBinError};
+
+/// 默认的解压后数据的最大字节数
+pub const MAX_DECOMPRESSED_SIZE: usize = 16 * 1024 * 1024;
+
+/// 压缩算法
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Compression {
+    /// 原始DEFLATE数据流（RFC 1951）
+    Deflate,
+    /// zlib格式（RFC 1950）
+    Zlib,
+    /// gzip格式（RFC 1952）
+    Gzip,
+    /// LZ4帧格式
+    Lz4,
+    /// Zstandard帧格式
+    Zstd,
+}
+
+impl Compression {
+    pub fn compress(&self, data: BitVec<u8, Msb0>) -> Result<BitVec<u8, Msb0>, WriteBinError> {
+        if !data.len().is_multiple_of(8) {
+            return Err(WriteBinError::CompressError(
+                "压缩数据必须全部为完整字节".to_string(),
+            ));
+        }
+        let data = data.as_raw_slice();
+        let err = |e: std::io::Error| WriteBinError::CompressError(e.to_string());
+
+        let ret = match self {
+            Self::Deflate => {
+                let mut e = flate2::write::DeflateEncoder::new(vec![], Default::default());
+                e.write_all(data).map_err(err)?;
+                e.finish().map_err(err)?
+            }
+            Self::Zlib => {
+                let mut e = flate2::write::ZlibEncoder::new(vec![], Default::default());
+                e.write_all(data).map_err(err)?;
+                e.finish().map_err(err)?
+            }
+            Self::Gzip => {
+                let mut e = flate2::write::GzEncoder::new(vec![], Default::default());
+                e.write_all(data).map_err(err)?;
+                e.finish().map_err(err)?
+            }
+            Self::Lz4 => {
+                let mut e = lz4_flex::frame::FrameEncoder::new(vec![]);
+                e.write_all(data).map_err(err)?;
+                e.finish()
+                    .map_err(|e| WriteBinError::CompressError(e.to_string()))?
+            }
+            Self::Zstd => zstd::encode_all(data, 0).map_err(err)?,
+        };
+        Ok(BitVec::from_vec(ret))
+    }
+
+    /// 解压数据，解压后的数据超过`limit`字节时失败，避免恶意数据解压后占用过多内存
+    pub fn decompress(
+        &self,
+        data: &BitSlice<u8, Msb0>,
+        limit: usize,
+    ) -> Result<BitVec<u8, Msb0>, ReadBinError> {
+        if !data.len().is_multiple_of(8) {
+            return Err(ReadBinError::DecompressError(
+                "压缩数据必须全部为完整字节".to_string(),
+            ));
+        }
+        let (_, data) = Vec::<u8>::read(data, Limit::new_bit_size(BitSize(data.len())))?;
+        let data = data.as_slice();
+        let err = |e: std::io::Error| ReadBinError::DecompressError(e.to_string());
+
+        let decoder: Box<dyn Read + '_> = match self {
+            Self::Deflate => Box::new(flate2::read::DeflateDecoder::new(data)),
+            Self::Zlib => Box::new(flate2::read::ZlibDecoder::new(data)),
+            Self::Gzip => Box::new(flate2::read::GzDecoder::new(data)),
+            Self::Lz4 => Box::new(lz4_flex::frame::FrameDecoder::new(data)),
+            Self::Zstd => Box::new(zstd::stream::read::Decoder::new(data).map_err(err)?),
+        };
+        let mut ret = vec![];
+        decoder
+            .take(limit as u64 + 1)
+            .read_to_end(&mut ret)
+            .map_err(err)?;
+        if ret.len() > limit {
+            return Err(ReadBinError::DecompressError(format!(
+                "解压后的数据超过{}字节",
+                limit
+            )));
+        }
+        Ok(BitVec::from_vec(ret))
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Deflate => "DEFLATE",
+            Self::Zlib => "zlib",
+            Self::Gzip => "gzip",
+            Self::Lz4 => "LZ4",
+            Self::Zstd => "Zstandard",
+        }
+    }
+}
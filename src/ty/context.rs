@@ -0,0 +1,362 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, VecDeque};
+
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+
+use crate::error::{RefError, WriteBinError};
+use crate::ty::{BitOrder, Endian, PointerBase, Unit, MAX_DECOMPRESSED_SIZE};
+use crate::Type;
+
+/// 引用类型的最大嵌套层数
+pub const MAX_REF_DEPTH: usize = 64;
+
+/// 读写时的选项，优先于结构体中的默认设置，但不会覆盖字段中明确指定的值
+///
+/// 可以使一个类型定义同时用于不同字节顺序的数据
+///
+/// ```rust
+/// use bin2json::bitvec::BitView;
+/// use bin2json::serde_json::json;
+/// use bin2json::ty::{Endian, Field, Options, Unit};
+/// use bin2json::Type;
+///
+/// let ty = Type::new_struct(vec![Field::new("v", Type::Uint16 { unit: Unit::default() })]);
+/// let data = [0x12u8, 0x34];
+/// assert_eq!(ty.read(data.view_bits())?.0, json!({ "v": 0x1234 }));
+///
+/// let options = Options { endian: Some(Endian::Little), ..Default::default() };
+/// assert_eq!(ty.read_with(data.view_bits(), &options)?.0, json!({ "v": 0x3412 }));
+/// # Ok::<_, bin2json::ReadBinError>(())
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct Options {
+    /// 未明确指定字节顺序的字段使用的字节顺序
+    pub endian: Option<Endian>,
+    /// 结构体中按位排列的字段在字节内的顺序
+    pub bit_order: Option<BitOrder>,
+    /// 读取[`Type::Compressed`]时解压后数据的最大字节数，默认为[`MAX_DECOMPRESSED_SIZE`]
+    pub max_decompressed_size: Option<usize>,
+}
+
+/// 类型定义的作用域
+struct Scope<'a> {
+    definitions: &'a BTreeMap<String, Type>,
+    parent: Option<&'a Scope<'a>>,
+}
+
+/// 写入时收集的被指向的数据
+#[derive(Default)]
+pub(crate) struct Pointers {
+    /// 被指向数据的写入结果
+    targets: Vec<BitVec<u8, Msb0>>,
+    /// 被指向数据的偏移量（字节），第二次写入时使用
+    offsets: Vec<usize>,
+    /// 第一次写入时内层结构体的写入结果
+    nested: VecDeque<Nested>,
+    /// 是否为第二次写入
+    replay: bool,
+}
+
+/// 内层结构体的写入结果
+struct Nested {
+    output: BitVec<u8, Msb0>,
+    /// 登记到整个数据流中的被指向数据
+    buffer_targets: Vec<BitVec<u8, Msb0>>,
+}
+
+/// 读写数据时的上下文
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Context<'a> {
+    scope: Option<&'a Scope<'a>>,
+    depth: usize,
+    options: Options,
+    /// 外层结构体中的默认字节顺序
+    endian: Option<Endian>,
+    /// 外层结构体中按位排列的字段的顺序
+    bit_order: Option<BitOrder>,
+    /// 读取时整个数据流的起始位置
+    buffer_start: Option<&'a BitSlice<u8, Msb0>>,
+    /// 读取时所在结构体的起始位置
+    struct_start: Option<&'a BitSlice<u8, Msb0>>,
+    /// 读取时所在结构体中被指向数据的结束位置（相对于结构体的起始位置）
+    struct_end: Option<&'a Cell<usize>>,
+    /// 写入时整个数据流中的指针
+    buffer_pointers: Option<&'a RefCell<Pointers>>,
+    /// 写入时所在结构体中的指针
+    struct_pointers: Option<&'a RefCell<Pointers>>,
+}
+
+impl<'a> Context<'a> {
+    /// 使用`options`读写时的上下文
+    pub fn with_options(options: &Options) -> Self {
+        Context {
+            options: *options,
+            ..Default::default()
+        }
+    }
+
+    /// 读取`data`时使用的上下文
+    pub fn with_buffer(&self, data: &'a BitSlice<u8, Msb0>) -> Self {
+        Context {
+            buffer_start: Some(data),
+            ..*self
+        }
+    }
+
+    /// 进入指定了默认设置的结构体，未指定的设置继承外层的值
+    pub fn with_defaults(&self, endian: Option<Endian>, bit_order: Option<BitOrder>) -> Self {
+        Context {
+            endian: endian.or(self.endian),
+            bit_order: bit_order.or(self.bit_order),
+            ..*self
+        }
+    }
+
+    /// `unit`实际使用的字节顺序
+    pub fn endian(&self, unit: &Unit) -> Endian {
+        self.endian_or(unit.endian)
+    }
+
+    /// 类型中指定的字节顺序`endian`实际使用的字节顺序
+    pub fn endian_or(&self, endian: Option<Endian>) -> Endian {
+        endian
+            .or(self.options.endian)
+            .or(self.endian)
+            .unwrap_or_default()
+    }
+
+    /// 解压后数据的最大字节数
+    pub fn max_decompressed_size(&self) -> usize {
+        self.options
+            .max_decompressed_size
+            .unwrap_or(MAX_DECOMPRESSED_SIZE)
+    }
+
+    /// 按位排列的字段在字节内的顺序
+    pub fn bit_order(&self) -> BitOrder {
+        self.options
+            .bit_order
+            .or(self.bit_order)
+            .unwrap_or_default()
+    }
+
+    /// 读取从`data`开始的结构体时使用的上下文。`end`用于记录结构体中被指向数据的结束位置
+    pub fn enter_struct<'b>(&self, data: &'b BitSlice<u8, Msb0>, end: &'b Cell<usize>) -> Context<'b>
+    where
+        'a: 'b,
+    {
+        Context {
+            struct_start: Some(data),
+            struct_end: Some(end),
+            ..*self
+        }
+    }
+
+    /// 记录结构体中被指向数据的结束位置
+    pub fn set_pointed_end(&self, base: PointerBase, end: usize) {
+        if let (PointerBase::Struct, Some(struct_end)) = (base, self.struct_end) {
+            struct_end.set(struct_end.get().max(end));
+        }
+    }
+
+    /// 指针偏移量的起始位置，未设置时为`data`
+    pub fn pointer_start<'b>(
+        &self,
+        base: PointerBase,
+        data: &'b BitSlice<u8, Msb0>,
+    ) -> &'b BitSlice<u8, Msb0>
+    where
+        'a: 'b,
+    {
+        let start = match base {
+            PointerBase::Buffer => self.buffer_start,
+            PointerBase::Struct => self.struct_start.or(self.buffer_start),
+        };
+        start.unwrap_or(data)
+    }
+
+    /// 写入数据，并将其中指针（偏移量起始位置为`base`）指向的数据依次放在之后
+    ///
+    /// 存在指针时，会使用计算得到的偏移量再次写入。内层结构体的写入结果不受外层偏移量的影响，
+    /// 外层结构体再次写入时直接使用第一次的结果，避免嵌套的层数越多写入的次数成倍增加
+    pub fn write_pointed<F>(&self, base: PointerBase, f: F) -> Result<BitVec<u8, Msb0>, WriteBinError>
+    where
+        F: FnMut(&Context) -> Result<BitVec<u8, Msb0>, WriteBinError>,
+    {
+        let parent = self.struct_pointers.filter(|_| base == PointerBase::Struct);
+        let replay = parent.is_some_and(|p| p.borrow().replay);
+        if let Some(parent) = parent.filter(|_| replay) {
+            let nested = parent.borrow_mut().nested.pop_front();
+            if let Some(Nested { output, buffer_targets }) = nested {
+                if let Some(outer) = self.buffer_pointers {
+                    outer.borrow_mut().targets.extend(buffer_targets);
+                }
+                return Ok(output);
+            }
+        }
+
+        let outer_len = self.buffer_pointers.map(|p| p.borrow().targets.len());
+        let out = self.write_twice(base, f)?;
+        if let Some(parent) = parent.filter(|_| !replay) {
+            let buffer_targets = self
+                .buffer_pointers
+                .zip(outer_len)
+                .map(|(p, len)| p.borrow().targets[len..].to_vec())
+                .unwrap_or_default();
+            parent.borrow_mut().nested.push_back(Nested {
+                output: out.clone(),
+                buffer_targets,
+            });
+        }
+        Ok(out)
+    }
+
+    fn write_twice<F>(&self, base: PointerBase, mut f: F) -> Result<BitVec<u8, Msb0>, WriteBinError>
+    where
+        F: FnMut(&Context) -> Result<BitVec<u8, Msb0>, WriteBinError>,
+    {
+        let pointers = RefCell::new(Pointers::default());
+        let ctx = match base {
+            PointerBase::Buffer => Context {
+                buffer_pointers: Some(&pointers),
+                ..*self
+            },
+            PointerBase::Struct => Context {
+                struct_pointers: Some(&pointers),
+                ..*self
+            },
+        };
+
+        // 外层登记的指针数量，再次写入前需要移除第一次写入时登记的指针
+        let outer = self.buffer_pointers.filter(|_| base == PointerBase::Struct);
+        let outer_len = outer.map(|p| p.borrow().targets.len());
+
+        let out = f(&ctx)?;
+        let first = pointers.take();
+        if first.targets.is_empty() {
+            return Ok(out);
+        }
+        if !out.len().is_multiple_of(8) {
+            return Err(WriteBinError::BytesSizeError);
+        }
+
+        let mut offset = out.len() / 8;
+        let offsets = first
+            .targets
+            .iter()
+            .map(|t| {
+                let o = offset;
+                offset += t.len() / 8;
+                o
+            })
+            .collect();
+        pointers.replace(Pointers {
+            targets: vec![],
+            offsets,
+            nested: first.nested,
+            replay: true,
+        });
+
+        if let (Some(outer), Some(len)) = (outer, outer_len) {
+            outer.borrow_mut().targets.truncate(len);
+        }
+        let mut second = f(&ctx)?;
+        if second.len() != out.len() {
+            return Err(WriteBinError::PointerError("偏移量的大小必须固定"));
+        }
+        for target in pointers.take().targets {
+            second.extend_from_bitslice(&target);
+        }
+        Ok(second)
+    }
+
+    /// 登记被指向数据的写入结果，返回该数据的偏移量。第一次写入时偏移量为0
+    pub fn add_pointer(
+        &self,
+        base: PointerBase,
+        target: BitVec<u8, Msb0>,
+    ) -> Result<usize, WriteBinError> {
+        if !target.len().is_multiple_of(8) {
+            return Err(WriteBinError::BytesSizeError);
+        }
+        let pointers = match base {
+            PointerBase::Buffer => self.buffer_pointers,
+            PointerBase::Struct => self.struct_pointers.or(self.buffer_pointers),
+        }
+        .ok_or(WriteBinError::PointerError("未找到指针所在的数据流"))?;
+
+        let mut pointers = pointers.borrow_mut();
+        let offset = pointers
+            .offsets
+            .get(pointers.targets.len())
+            .copied()
+            .unwrap_or(0);
+        pointers.targets.push(target);
+        Ok(offset)
+    }
+
+    /// 在包含`definitions`的作用域中执行`f`
+    pub fn with_definitions<R>(
+        &self,
+        definitions: &BTreeMap<String, Type>,
+        f: impl FnOnce(&Context) -> R,
+    ) -> R {
+        if definitions.is_empty() {
+            return f(self);
+        }
+
+        let scope = Scope {
+            definitions,
+            parent: self.scope,
+        };
+        f(&Context {
+            scope: Some(&scope),
+            ..*self
+        })
+    }
+
+    /// 从内到外查找类型定义，返回类型定义以及定义所在的作用域
+    fn get(&self, name: &str) -> Option<(&'a Type, &'a Scope<'a>)> {
+        let mut scope = self.scope;
+        while let Some(s) = scope {
+            if let Some(ty) = s.definitions.get(name) {
+                return Some((ty, s));
+            }
+            scope = s.parent;
+        }
+        None
+    }
+
+    /// 获取引用的类型定义，以及用于读写该类型的上下文
+    ///
+    /// 类型定义中的引用在定义所在的作用域中查找，不受引用处的内层定义影响
+    pub fn enter(&self, name: &str) -> Result<(&'a Type, Context<'a>), RefError> {
+        if self.depth >= MAX_REF_DEPTH {
+            return Err(RefError::TooDeep);
+        }
+        let (ty, scope) = self
+            .get(name)
+            .ok_or_else(|| RefError::NotFound(name.to_string()))?;
+        Ok((
+            ty,
+            Context {
+                scope: Some(scope),
+                depth: self.depth + 1,
+                ..*self
+            },
+        ))
+    }
+
+    /// 如果类型为[`Type::Ref`]，则返回最终引用的类型定义以及对应的上下文，否则返回输入的类型
+    pub fn resolve<'t>(&self, ty: &'t Type) -> Result<(&'t Type, Context<'a>), RefError>
+    where
+        'a: 't,
+    {
+        let mut ty = ty;
+        let mut ctx = *self;
+        while let Type::Ref { name } = ty {
+            (ty, ctx) = ctx.enter(name)?;
+        }
+        Ok((ty, ctx))
+    }
+}
@@ -0,0 +1,186 @@
+use crate::error::{ReadBinError, WriteBinError};
+
+/// 字符串编码
+///
+/// **示例：**
+/// ```rust
+/// use bin2json::ty::Encoding;
+///
+/// let encoding: Encoding = serde_json::from_str(r#""Utf16Le""#)?;
+/// assert_eq!(Encoding::Utf16Le, encoding);
+/// assert_eq!(vec![0x41, 0x00], encoding.encode("A", false).unwrap());
+/// assert_eq!("A", encoding.decode(&[0x41, 0x00], false).unwrap());
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Ascii,
+    /// ISO-8859-1
+    Latin1,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+    Gbk,
+    Gb18030,
+}
+
+impl Encoding {
+    /// 将数据解码为字符串
+    ///
+    /// `lossy`为`true`时使用`U+FFFD`替换无效的数据，否则返回错误
+    pub fn decode(&self, data: &[u8], lossy: bool) -> Result<String, ReadBinError> {
+        let error = || ReadBinError::DecodeError(self.name());
+
+        match self {
+            Encoding::Utf8 => {
+                if lossy {
+                    Ok(String::from_utf8_lossy(data).into_owned())
+                } else {
+                    Ok(std::str::from_utf8(data)?.to_string())
+                }
+            }
+            Encoding::Ascii => data
+                .iter()
+                .map(|b| {
+                    if b.is_ascii() {
+                        Ok(*b as char)
+                    } else if lossy {
+                        Ok(char::REPLACEMENT_CHARACTER)
+                    } else {
+                        Err(error())
+                    }
+                })
+                .collect(),
+            Encoding::Latin1 => Ok(data.iter().map(|b| *b as char).collect()),
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let units = data.chunks(2).map(|c| match (self, c) {
+                    (Encoding::Utf16Le, [l, h]) => u16::from_le_bytes([*l, *h]),
+                    (_, [h, l]) => u16::from_be_bytes([*h, *l]),
+                    // 不完整的编码单元按孤立的代理项处理
+                    _ => 0xDC00,
+                });
+                char::decode_utf16(units)
+                    .map(|c| match c {
+                        Ok(c) => Ok(c),
+                        Err(_) if lossy => Ok(char::REPLACEMENT_CHARACTER),
+                        Err(_) => Err(error()),
+                    })
+                    .collect()
+            }
+            Encoding::Utf32Le | Encoding::Utf32Be => {
+                data.chunks(4)
+                    .map(|c| {
+                        let c = match (self, c) {
+                            (Encoding::Utf32Le, [b0, b1, b2, b3]) => {
+                                char::from_u32(u32::from_le_bytes([*b0, *b1, *b2, *b3]))
+                            }
+                            (_, [b0, b1, b2, b3]) => {
+                                char::from_u32(u32::from_be_bytes([*b0, *b1, *b2, *b3]))
+                            }
+                            _ => None,
+                        };
+                        match c {
+                            Some(c) => Ok(c),
+                            None if lossy => Ok(char::REPLACEMENT_CHARACTER),
+                            None => Err(error()),
+                        }
+                    })
+                    .collect()
+            }
+            Encoding::Gbk | Encoding::Gb18030 => {
+                let encoding = self.encoding_rs();
+                if lossy {
+                    Ok(encoding.decode_without_bom_handling(data).0.into_owned())
+                } else {
+                    encoding
+                        .decode_without_bom_handling_and_without_replacement(data)
+                        .map(|s| s.into_owned())
+                        .ok_or_else(error)
+                }
+            }
+        }
+    }
+
+    /// 将字符串编码为数据
+    ///
+    /// `lossy`为`true`时使用`?`替换无法编码的字符，否则返回错误
+    pub fn encode(&self, s: &str, lossy: bool) -> Result<Vec<u8>, WriteBinError> {
+        let error = || WriteBinError::EncodeError(self.name());
+
+        match self {
+            Encoding::Utf8 => Ok(s.as_bytes().to_vec()),
+            Encoding::Ascii | Encoding::Latin1 => {
+                let max = if let Encoding::Ascii = self { 0x7F } else { 0xFF };
+                s.chars()
+                    .map(|c| {
+                        if c as u32 <= max {
+                            Ok(c as u8)
+                        } else if lossy {
+                            Ok(b'?')
+                        } else {
+                            Err(error())
+                        }
+                    })
+                    .collect()
+            }
+            Encoding::Utf16Le => Ok(s.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()),
+            Encoding::Utf16Be => Ok(s.encode_utf16().flat_map(|u| u.to_be_bytes()).collect()),
+            Encoding::Utf32Le => Ok(s.chars().flat_map(|c| (c as u32).to_le_bytes()).collect()),
+            Encoding::Utf32Be => Ok(s.chars().flat_map(|c| (c as u32).to_be_bytes()).collect()),
+            Encoding::Gbk | Encoding::Gb18030 => {
+                let mut encoder = self.encoding_rs().new_encoder();
+                let mut out = Vec::with_capacity(
+                    encoder
+                        .max_buffer_length_from_utf8_without_replacement(s.len())
+                        .unwrap_or(s.len() * 4),
+                );
+                let mut src = s;
+                loop {
+                    let (result, read) =
+                        encoder.encode_from_utf8_to_vec_without_replacement(src, &mut out, true);
+                    src = &src[read..];
+                    match result {
+                        encoding_rs::EncoderResult::InputEmpty => break,
+                        encoding_rs::EncoderResult::OutputFull => out.reserve(src.len() * 4),
+                        encoding_rs::EncoderResult::Unmappable(_) if lossy => out.push(b'?'),
+                        encoding_rs::EncoderResult::Unmappable(_) => return Err(error()),
+                    }
+                }
+                Ok(out)
+            }
+        }
+    }
+
+    /// 编码单元的字节数
+    pub const fn unit_size(&self) -> usize {
+        match self {
+            Encoding::Utf16Le | Encoding::Utf16Be => 2,
+            Encoding::Utf32Le | Encoding::Utf32Be => 4,
+            _ => 1,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Ascii => "ASCII",
+            Encoding::Latin1 => "Latin-1",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Utf32Le => "UTF-32LE",
+            Encoding::Utf32Be => "UTF-32BE",
+            Encoding::Gbk => "GBK",
+            Encoding::Gb18030 => "GB18030",
+        }
+    }
+
+    fn encoding_rs(&self) -> &'static encoding_rs::Encoding {
+        match self {
+            Encoding::Gb18030 => encoding_rs::GB18030,
+            _ => encoding_rs::GBK,
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use serde_json::{Map, Value};
+
+use crate::ty::utils::siblings_ctx;
 use crate::Type;
 
 /// 结构字段
@@ -23,12 +26,44 @@ use crate::Type;
 ///     Type::Uint16 { unit: Unit::new(Endian::Big, BitSize(8)) }
 /// );
 /// assert_eq!(field, serde_json::from_str::<Field>(json)?);
+///
+/// let json = r#"
+/// {
+///     "name": "ext_header",
+///     "if": "self.has_ext",
+///     "type": "Uint32"
+/// }
+/// "#;
+/// let field = Field::new("ext_header", Type::Uint32 { unit: Unit::default() }).with_condition("self.has_ext");
+/// assert_eq!(field, serde_json::from_str::<Field>(json)?);
+///
+/// let json = r#"
+/// {
+///     "name": "end_flag",
+///     "assert": "self.end_flag == self.start_flag",
+///     "type": "Uint8"
+/// }
+/// "#;
+/// let field = Field::new("end_flag", Type::uint8()).with_assert("self.end_flag == self.start_flag");
+/// assert_eq!(field, serde_json::from_str::<Field>(json)?);
 /// # Ok::<_, serde_json::Error>(())
 /// ```
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Field {
     /// 字段名称
     pub name: String,
+    /// 字段存在的条件。表达式结果应为布尔值，为`false`时读写都会跳过该字段，且读取结果中不包含该字段
+    ///
+    /// 执行表达式时可以使用同级且在本字段之前的字段的值，变量名为`self.field_name`，其中整数为整型变量，其他数值为浮点数。
+    /// 写入时同样只使用本字段之前的字段，未输入值的字段（如自动计算的长度）使用计算得到的值；输入值中不存在本字段时同样跳过该字段
+    #[serde(default, rename = "if", skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// 字段值需要满足的断言。表达式结果应为布尔值，为`false`时读写失败
+    ///
+    /// 读取时在读取本字段之后执行，可以使用同级且在本字段之前（包括本字段）的字段的值，变量名与`condition`相同。
+    /// 写入时在所有字段写入之后执行，同样只使用本字段之前（包括本字段）的字段，未输入值的字段（如自动计算的长度）使用计算得到的值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assert: Option<String>,
     /// 字段类型
     #[serde(flatten)]
     pub ty: Type,
@@ -39,6 +74,37 @@ impl Field {
         Self {
             name: name.into(),
             ty,
+            condition: None,
+            assert: None,
+        }
+    }
+
+    pub fn with_condition<S: Into<String>>(mut self, condition: S) -> Self {
+        self.condition = Some(condition.into());
+        self
+    }
+
+    pub fn with_assert<S: Into<String>>(mut self, assert: S) -> Self {
+        self.assert = Some(assert.into());
+        self
+    }
+
+    /// 使用同级字段的值判断字段是否存在
+    pub(crate) fn exists(&self, siblings: &Map<String, Value>) -> evalexpr::EvalexprResult<bool> {
+        if let Some(expr) = &self.condition {
+            let ctx = siblings_ctx(siblings)?;
+            evalexpr::eval_boolean_with_context(expr, &ctx)
+        } else {
+            Ok(true)
+        }
+    }
+
+    /// 使用同级字段的值检查断言是否成立
+    pub(crate) fn holds(&self, siblings: &Map<String, Value>) -> evalexpr::EvalexprResult<bool> {
+        if let Some(expr) = &self.assert {
+            evalexpr::eval_boolean_with_context(expr, &siblings_ctx(siblings)?)
+        } else {
+            Ok(true)
         }
     }
 }
@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+use deku::{DekuRead, DekuWrite};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+use crate::error::{ReadBinError, WriteBinError};
+use crate::ty::{BitSize, Endian, Unit};
+use crate::Value;
+
+const TYPE_NAME: &str = "Flags";
+
+/// 位标志的输出格式
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub enum FlagsFormat {
+    /// 已置位的标志名称数组，如：`["ready", "error"]`
+    #[default]
+    List,
+    /// 所有标志名称与是否置位组成的对象，如：`{ "ready": true, "busy": false, "error": true }`
+    Object,
+}
+
+/// 反序列化比特位与标志名称的映射
+///
+/// 内部标记的枚举无法直接将字符串形式的键反序列化为整数，因此需要手动转换
+pub fn de_flags<'de, D>(de: D) -> Result<BTreeMap<u8, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    BTreeMap::<String, String>::deserialize(de)?
+        .into_iter()
+        .map(|(bit, name)| {
+            bit.trim()
+                .parse()
+                .map(|bit| (bit, name))
+                .map_err(|_| D::Error::custom(format!("无效的比特位: {}", bit)))
+        })
+        .collect()
+}
+
+/// 位标志的总比特数，默认为8
+fn flags_size(unit: &Unit) -> BitSize {
+    unit.size.unwrap_or(BitSize(8))
+}
+
+pub fn read_flags<'a>(
+    data: &'a BitSlice<u8, Msb0>,
+    unit: &Unit,
+    endian: Endian,
+    flags: &BTreeMap<u8, String>,
+    format: FlagsFormat,
+) -> Result<(Value, &'a BitSlice<u8, Msb0>), ReadBinError> {
+    let (data, raw) = u64::read(data, (endian.into(), flags_size(unit)))?;
+    let is_set = |bit: &u8| raw.checked_shr(*bit as u32).unwrap_or(0) & 1 == 1;
+
+    let value = match format {
+        FlagsFormat::List => flags
+            .iter()
+            .filter(|(bit, _)| is_set(bit))
+            .map(|(_, name)| Value::from(name.as_str()))
+            .collect(),
+        FlagsFormat::Object => flags
+            .iter()
+            .map(|(bit, name)| (name.clone(), is_set(bit).into()))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+    };
+    Ok((value, data))
+}
+
+pub fn write_flags(
+    value: &Value,
+    unit: &Unit,
+    endian: Endian,
+    flags: &BTreeMap<u8, String>,
+    output: &mut BitVec<u8, Msb0>,
+) -> Result<(), WriteBinError> {
+    let size = flags_size(unit);
+    let bit_of = |name: &str| -> Result<u64, WriteBinError> {
+        let bit = flags
+            .iter()
+            .find_map(|(bit, n)| if n == name { Some(*bit) } else { None })
+            .ok_or_else(|| WriteBinError::UnknownFlag(name.to_string()))?;
+        if (bit as usize) < size.0 && bit < 64 {
+            Ok(1 << bit)
+        } else {
+            Err(WriteBinError::ValueOverflowOf(TYPE_NAME))
+        }
+    };
+
+    let mut raw = 0u64;
+    match value {
+        Value::Array(list) => {
+            for name in list {
+                let name = name.as_str().ok_or(WriteBinError::TypeError(TYPE_NAME))?;
+                raw |= bit_of(name)?;
+            }
+        }
+        Value::Object(map) => {
+            for (name, set) in map {
+                let bit = bit_of(name)?;
+                if set.as_bool().ok_or(WriteBinError::TypeError(TYPE_NAME))? {
+                    raw |= bit;
+                }
+            }
+        }
+        _ => return Err(WriteBinError::TypeError(TYPE_NAME)),
+    }
+
+    raw.write(output, (endian.into(), size))?;
+    Ok(())
+}
@@ -1,37 +1,71 @@
+use std::collections::BTreeMap;
+use std::num::IntErrorKind;
+
+use chrono::FixedOffset;
 use deku::bitvec::{BitSlice, Msb0};
 use deku::ctx::Limit;
 pub use deku::ctx::{BitSize, ByteSize};
 use deku::prelude::*;
+use half::{bf16, f16};
 use serde_json::Map;
 
-pub use array_length::Length;
+use address::{read_address, write_address, Address};
+pub use array_length::{Length, Terminator};
+use bcd::{read_bcd, write_bcd};
+pub use bit_order::BitOrder;
 pub use bytes_size::BytesSize;
 pub use checksum::Checksum;
+pub use compression::{Compression, MAX_DECOMPRESSED_SIZE};
+pub use context::{Options, MAX_REF_DEPTH};
+use context::Context;
 pub use converter::Converter;
+pub use encoding::Encoding;
 pub use endian::Endian;
 pub use field::Field;
+use flags::{read_flags, write_flags};
+pub use flags::FlagsFormat;
+pub use pointer::PointerBase;
+use pointer::{read_pointer, write_pointer};
 use read_array::read_array;
 use read_struct::read_struct;
+pub use rounding::Rounding;
+pub use timestamp::{Epoch, Resolution};
+use timestamp::{from_rfc3339, to_rfc3339};
+pub use tlv::TlvFormat;
+use tlv::{read_tlv, write_tlv};
 pub use unit::Unit;
 use utils::get_data_by_size;
+use var_int::{read_var_int, write_var_int};
 
 use crate::bitvec::BitVec;
 use crate::error::{ReadBinError, WriteBinError};
-use crate::range::KeyRangeMap;
+use crate::range::{KeyRange, KeyRangeMap};
 use crate::secure::SecureKey;
 use crate::ty::write_struct::write_struct;
 use crate::Value;
 
+mod address;
 mod array_length;
+mod bcd;
+mod bit_order;
 mod bytes_size;
 mod checksum;
+mod compression;
+mod context;
 mod converter;
+mod encoding;
 mod endian;
 mod field;
+mod flags;
+mod pointer;
 mod read_array;
 mod read_struct;
+mod rounding;
+mod timestamp;
+mod tlv;
 mod unit;
 mod utils;
+mod var_int;
 mod write_struct;
 
 /// 数据类型
@@ -51,6 +85,58 @@ pub enum Type {
     /// ```
     Magic { magic: Vec<u8> },
 
+    /// 保留或填充数据
+    ///
+    /// 读取时跳过指定大小的数据，作为[`Type::Struct`]的字段时不会出现在读取结果中。写入时忽略输入值并使用`fill`循环填充
+    ///
+    /// ```rust
+    /// use bin2json::ty::BitSize;
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Padding",
+    ///     "size": { "type": "Bytes", "value": 3 },
+    ///     "fill": [255]
+    /// }"#;
+    /// assert_eq!(Type::Padding { size: BitSize(24), fill: vec![0xFF] }, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Padding {
+        /// 数据大小
+        #[serde(serialize_with = "unit::se_size")]
+        #[serde(deserialize_with = "unit::de_size")]
+        size: BitSize,
+        /// 写入时的填充内容，为空时填充0
+        #[serde(default)]
+        fill: Vec<u8>,
+    },
+
+    /// 对齐
+    ///
+    /// **注意：** 该类型应定义于结构体之中
+    ///
+    /// 跳过数据直到当前位置（相对于结构体的起始位置）为`align`的整数倍，作为[`Type::Struct`]的字段时不会出现在读取结果中。
+    /// 写入时忽略输入值并使用`fill`循环填充
+    ///
+    /// ```rust
+    /// use bin2json::ty::BitSize;
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Align",
+    ///     "align": { "type": "Bytes", "value": 4 }
+    /// }"#;
+    /// assert_eq!(Type::align(BitSize(32)), serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Align {
+        /// 对齐的大小
+        #[serde(serialize_with = "unit::se_size")]
+        #[serde(deserialize_with = "unit::de_size")]
+        align: BitSize,
+        /// 写入时的填充内容，为空时填充0
+        #[serde(default)]
+        fill: Vec<u8>,
+    },
+
     /// 布尔型数据。
     ///
     /// ```rust
@@ -85,12 +171,12 @@ pub enum Type {
     /// 有符号16位整数
     ///
     /// ```rust
-    /// use bin2json::ty::Endian;
+    /// use bin2json::ty::Unit;
     /// use bin2json::Type;
     /// let json = r#"{
     ///     "type": "Int16"
     /// }"#;
-    /// assert_eq!(Type::int16(Endian::Big), serde_json::from_str(json)?);
+    /// assert_eq!(Type::Int16 { unit: Unit::default() }, serde_json::from_str(json)?);
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     Int16 {
@@ -101,12 +187,12 @@ pub enum Type {
     /// 有符号32位整数
     ///
     /// ```rust
-    /// use bin2json::ty::Endian;
+    /// use bin2json::ty::Unit;
     /// use bin2json::Type;
     /// let json = r#"{
     ///     "type": "Int32"
     /// }"#;
-    /// assert_eq!(Type::int32(Endian::Big), serde_json::from_str(json)?);
+    /// assert_eq!(Type::Int32 { unit: Unit::default() }, serde_json::from_str(json)?);
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     Int32 {
@@ -117,12 +203,12 @@ pub enum Type {
     /// 有符号64位整数
     ///
     /// ```rust
-    /// use bin2json::ty::Endian;
+    /// use bin2json::ty::Unit;
     /// use bin2json::Type;
     /// let json = r#"{
     ///     "type": "Int64"
     /// }"#;
-    /// assert_eq!(Type::int64(Endian::Big), serde_json::from_str(json)?);
+    /// assert_eq!(Type::Int64 { unit: Unit::default() }, serde_json::from_str(json)?);
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     Int64 {
@@ -130,6 +216,24 @@ pub enum Type {
         unit: Unit,
     },
 
+    /// 有符号128位整数
+    ///
+    /// 由于JSON数值无法无损表示128位整数，读取时输出为十进制字符串。写入时可以是字符串或数值
+    ///
+    /// ```rust
+    /// use bin2json::ty::Unit;
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Int128"
+    /// }"#;
+    /// assert_eq!(Type::Int128 { unit: Unit::default() }, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Int128 {
+        #[serde(default)]
+        unit: Unit,
+    },
+
     /// 无符号8位整数
     ///
     /// ```rust
@@ -148,12 +252,12 @@ pub enum Type {
     /// 无符号16位整数
     ///
     /// ```rust
-    /// use bin2json::ty::Endian;
+    /// use bin2json::ty::Unit;
     /// use bin2json::Type;
     /// let json = r#"{
     ///     "type": "Uint16"
     /// }"#;
-    /// assert_eq!(Type::uint16(Endian::Big), serde_json::from_str(json)?);
+    /// assert_eq!(Type::Uint16 { unit: Unit::default() }, serde_json::from_str(json)?);
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     Uint16 {
@@ -164,12 +268,12 @@ pub enum Type {
     /// 无符号32位整数
     ///
     /// ```rust
-    /// use bin2json::ty::Endian;
+    /// use bin2json::ty::Unit;
     /// use bin2json::Type;
     /// let json = r#"{
     ///     "type": "Uint32"
     /// }"#;
-    /// assert_eq!(Type::uint32(Endian::Big), serde_json::from_str(json)?);
+    /// assert_eq!(Type::Uint32 { unit: Unit::default() }, serde_json::from_str(json)?);
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     Uint32 {
@@ -180,12 +284,12 @@ pub enum Type {
     /// 无符号64位整数
     ///
     /// ```rust
-    /// use bin2json::ty::Endian;
+    /// use bin2json::ty::Unit;
     /// use bin2json::Type;
     /// let json = r#"{
     ///     "type": "Uint64"
     /// }"#;
-    /// assert_eq!(Type::uint64(Endian::Big), serde_json::from_str(json)?);
+    /// assert_eq!(Type::Uint64 { unit: Unit::default() }, serde_json::from_str(json)?);
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     Uint64 {
@@ -193,53 +297,339 @@ pub enum Type {
         unit: Unit,
     },
 
-    /// 单精度浮点数
+    /// 无符号128位整数
+    ///
+    /// 由于JSON数值无法无损表示128位整数，读取时输出为十进制字符串。写入时可以是字符串或数值
+    ///
+    /// ```rust
+    /// use bin2json::ty::Unit;
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Uint128"
+    /// }"#;
+    /// assert_eq!(Type::Uint128 { unit: Unit::default() }, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Uint128 {
+        #[serde(default)]
+        unit: Unit,
+    },
+
+    /// 变长整数(LEB128)
+    ///
+    /// 每个字节的低7位为数据，最高位表示后面是否还有数据。写入时总是使用最短的编码
+    ///
+    /// ```rust
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "VarInt",
+    ///     "signed": true,
+    ///     "zigzag": true
+    /// }"#;
+    /// assert_eq!(Type::var_int(true, true), serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    VarInt {
+        /// 是否为有符号整数。有符号整数默认使用符号扩展(SLEB128)
+        #[serde(default)]
+        signed: bool,
+        /// 有符号整数是否使用ZigZag编码(protobuf中的`sint32`/`sint64`)
+        #[serde(default)]
+        zigzag: bool,
+    },
+
+    /// 半精度浮点数(IEEE 754 binary16)
     ///
     /// ```rust
     /// use bin2json::ty::Endian;
     /// use bin2json::Type;
     /// let json = r#"{
+    ///     "type": "Float16",
+    ///     "endian": "Little"
+    /// }"#;
+    /// assert_eq!(Type::float16(Endian::Little), serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Float16 {
+        /// 字节顺序，未指定时使用所在结构体的默认字节顺序
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        endian: Option<Endian>,
+    },
+
+    /// 脑浮点数(bfloat16)。即截取单精度浮点数的高16位
+    ///
+    /// ```rust
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "BFloat16"
+    /// }"#;
+    /// assert_eq!(Type::BFloat16 { endian: None }, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    BFloat16 {
+        /// 字节顺序，未指定时使用所在结构体的默认字节顺序
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        endian: Option<Endian>,
+    },
+
+    /// 单精度浮点数
+    ///
+    /// ```rust
+    /// use bin2json::Type;
+    /// let json = r#"{
     ///     "type": "Float32"
     /// }"#;
-    /// assert_eq!(Type::float32(Endian::Big), serde_json::from_str(json)?);
+    /// assert_eq!(Type::Float32 { endian: None }, serde_json::from_str(json)?);
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     Float32 {
-        #[serde(default)]
-        endian: Endian,
+        /// 字节顺序，未指定时使用所在结构体的默认字节顺序
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        endian: Option<Endian>,
     },
 
     /// 双精度浮点数
     ///
     /// ```rust
-    /// use bin2json::ty::Endian;
     /// use bin2json::Type;
     /// let json = r#"{
     ///     "type": "Float64"
     /// }"#;
-    /// assert_eq!(Type::float64(Endian::Big), serde_json::from_str(json)?);
+    /// assert_eq!(Type::Float64 { endian: None }, serde_json::from_str(json)?);
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     Float64 {
+        /// 字节顺序，未指定时使用所在结构体的默认字节顺序
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        endian: Option<Endian>,
+    },
+
+    /// 定点数(Q格式)
+    ///
+    /// 读取时将原始整数除以`2^fraction_bits`并输出为浮点数，写入时按`rounding`取整后写入原始整数
+    ///
+    /// ```rust
+    /// use bin2json::ty::{Endian, Rounding, Unit};
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Fixed",
+    ///     "signed": true,
+    ///     "integer_bits": 16,
+    ///     "fraction_bits": 16,
+    ///     "rounding": "Floor"
+    /// }"#;
+    /// assert_eq!(Type::Fixed {
+    ///     signed: true,
+    ///     integer_bits: 16,
+    ///     fraction_bits: 16,
+    ///     unit: Unit::default(),
+    ///     rounding: Rounding::Floor,
+    /// }, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Fixed {
+        /// 是否为有符号数
+        #[serde(default)]
+        signed: bool,
+        /// 整数部分的比特数。有符号数包括符号位
+        integer_bits: usize,
+        /// 小数部分的比特数
+        fraction_bits: usize,
+        /// `unit.size`未设置时大小为`integer_bits + fraction_bits`
+        #[serde(default)]
+        unit: Unit,
+        /// 写入时的取整方式
+        #[serde(default)]
+        rounding: Rounding,
+    },
+
+    /// BCD码
+    ///
+    /// 读取结果为整数，超出64位整数范围或`as_string`为`true`时为十进制字符串。写入时可以是整数或十进制字符串，位数不足时在前面补0
+    ///
+    /// ```rust
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Bcd",
+    ///     "digits": 6,
+    ///     "signed": true
+    /// }"#;
+    /// assert_eq!(Type::Bcd {
+    ///     digits: 6,
+    ///     unpacked: false,
+    ///     signed: true,
+    ///     endian: None,
+    ///     as_string: false,
+    /// }, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Bcd {
+        /// 数字位数
+        digits: usize,
+        /// 是否为非压缩BCD码。压缩BCD码每个字节存放2位数字，非压缩BCD码每个字节的低4位存放1位数字
+        #[serde(default)]
+        unpacked: bool,
+        /// 是否在最后带有符号半字节。正数为`0xC`，负数为`0xD`
+        #[serde(default)]
+        signed: bool,
+        /// 字节顺序，未指定时使用所在结构体的默认字节顺序
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        endian: Option<Endian>,
+        /// 是否以字符串的形式输出。用于保留前导零
+        #[serde(default)]
+        as_string: bool,
+    },
+
+    /// 位标志
+    ///
+    /// 读取结果为已置位的标志名称数组或由标志名称与是否置位组成的对象，写入时两种格式都可以使用。
+    /// 未命名的比特位在读取时忽略，写入时为0
+    ///
+    /// ```rust
+    /// use bin2json::ty::{BitSize, Endian, FlagsFormat, Unit};
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Flags",
+    ///     "unit": { "endian": "Little", "size": { "type": "Bytes", "value": 2 } },
+    ///     "flags": {
+    ///         "0": "ready",
+    ///         "1": "busy",
+    ///         "15": "error"
+    ///     },
+    ///     "format": "Object"
+    /// }"#;
+    /// let mut ty = Type::flags(
+    ///     Unit::new(Endian::Little, BitSize(16)),
+    ///     [(0, "ready"), (1, "busy"), (15, "error")],
+    /// );
+    /// if let Type::Flags { format, .. } = &mut ty {
+    ///     *format = FlagsFormat::Object;
+    /// }
+    /// assert_eq!(ty, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Flags {
+        /// `unit.size`未设置时大小为8比特
+        #[serde(default)]
+        unit: Unit,
+        /// 比特位（最低位为0）对应的标志名称
+        #[serde(deserialize_with = "flags::de_flags")]
+        flags: BTreeMap<u8, String>,
+        /// 读取结果的格式
+        #[serde(default)]
+        format: FlagsFormat,
+    },
+
+    /// IPv4地址，大小为4字节
+    ///
+    /// 读取结果为点分十进制格式的字符串，如：`192.168.0.1`
+    ///
+    /// ```rust
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "IPv4"
+    /// }"#;
+    /// assert_eq!(Type::Ipv4, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    #[serde(rename = "IPv4")]
+    Ipv4,
+
+    /// IPv6地址，大小为16字节
+    ///
+    /// 读取结果为RFC 5952格式的字符串，如：`2001:db8::1`
+    ///
+    /// ```rust
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "IPv6"
+    /// }"#;
+    /// assert_eq!(Type::Ipv6, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    #[serde(rename = "IPv6")]
+    Ipv6,
+
+    /// MAC地址，大小为6字节
+    ///
+    /// 读取结果为以`:`分隔的小写十六进制字符串，如：`aa:bb:cc:dd:ee:ff`。写入时也可以使用`-`分隔
+    ///
+    /// ```rust
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "MAC"
+    /// }"#;
+    /// assert_eq!(Type::Mac, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    #[serde(rename = "MAC")]
+    Mac,
+
+    /// UUID，大小为16字节
+    ///
+    /// 读取结果为带连字符的小写十六进制字符串，如：`123e4567-e89b-12d3-a456-426614174000`
+    ///
+    /// ```rust
+    /// use bin2json::ty::Endian;
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "UUID",
+    ///     "endian": "Little"
+    /// }"#;
+    /// assert_eq!(Type::uuid(Endian::Little), serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    #[serde(rename = "UUID")]
+    Uuid {
+        /// 字节顺序。为[`Endian::Little`]时前三组按小端序存放，即Microsoft GUID的字节顺序
         #[serde(default)]
         endian: Endian,
     },
 
-    /// UTF8字符串
+    /// 字符串
     ///
     /// ```rust
-    /// use bin2json::ty::BytesSize;
+    /// use bin2json::ty::{BytesSize, Encoding};
     /// use bin2json::Type;
     /// let json = r#"{
     ///     "type": "String",
     ///     "size": 64
     /// }"#;
     /// assert_eq!(Type::string(BytesSize::Fixed(64)), serde_json::from_str(json)?);
+    ///
+    /// let json = r#"{
+    ///     "type": "String",
+    ///     "size": 64,
+    ///     "encoding": "Gbk",
+    ///     "lossy": true
+    /// }"#;
+    /// assert_eq!(Type::String {
+    ///     size: Some(BytesSize::Fixed(64)),
+    ///     encoding: Encoding::Gbk,
+    ///     lossy: true,
+    ///     padding: None,
+    /// }, serde_json::from_str(json)?);
+    ///
+    /// let json = r#"{
+    ///     "type": "String",
+    ///     "size": 32,
+    ///     "padding": 0
+    /// }"#;
+    /// assert_eq!(Type::padded_string(32, 0), serde_json::from_str(json)?);
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     String {
         #[serde(default)]
         size: Option<BytesSize>,
+        /// 字符串编码，默认为UTF-8
+        #[serde(default)]
+        encoding: Encoding,
+        /// 是否替换无效的数据或无法编码的字符，而不是返回错误
+        #[serde(default)]
+        lossy: bool,
+        /// 填充字节。读取时去除末尾的填充字节，写入固定大小的字符串时使用该字节补足长度
+        #[serde(default)]
+        padding: Option<u8>,
     },
 
     /// 二进制数据
@@ -252,17 +642,27 @@ pub enum Type {
     ///     "size": "len by field"
     /// }"#;
     /// assert_eq!(Type::bin(BytesSize::new("len by field")), serde_json::from_str(json)?);
+    ///
+    /// let json = r#"{
+    ///     "type": "Bin",
+    ///     "size": 16,
+    ///     "padding": 255
+    /// }"#;
+    /// assert_eq!(Type::padded_bin(16, 0xFF), serde_json::from_str(json)?);
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     Bin {
         #[serde(default)]
         size: Option<BytesSize>,
+        /// 填充字节。读取时去除末尾的填充字节，写入固定大小的数据时使用该字节补足长度
+        #[serde(default)]
+        padding: Option<u8>,
     },
 
     /// 结构体
     ///
     /// ```rust
-    /// use bin2json::ty::Field;
+    /// use bin2json::ty::{BitOrder, Endian, Field};
     /// use bin2json::Type;
     /// let json = r#"{
     ///     "type": "Struct",
@@ -271,6 +671,17 @@ pub enum Type {
     ///     ]
     /// }"#;
     /// assert_eq!(Type::new_struct(vec![Field::new("f1", Type::uint8())]), serde_json::from_str(json)?);
+    ///
+    /// let json = r#"{
+    ///     "type": "Struct",
+    ///     "default_endian": "Little",
+    ///     "bit_order": "Lsb0",
+    ///     "fields": []
+    /// }"#;
+    /// assert_eq!(
+    ///     Type::new_struct(vec![]).with_default_endian(Endian::Little).with_bit_order(BitOrder::Lsb0),
+    ///     serde_json::from_str(json)?
+    /// );
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     Struct {
@@ -279,6 +690,16 @@ pub enum Type {
         /// 手动指定结构的总字节大小
         #[serde(default)]
         size: Option<BytesSize>,
+        /// 命名的类型定义，可在本结构及其所有子类型中通过[`Type::Ref`]引用
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        definitions: BTreeMap<String, Type>,
+        /// 本结构及其所有子类型中未指定字节顺序的[`Unit`]及浮点数使用的字节顺序，未指定时继承外层结构，默认为[`Endian::Big`]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default_endian: Option<Endian>,
+        /// 按位排列的字段（布尔型、整数、[`Type::Flags`]及以其为原始类型的[`Type::Mapping`]）在字节内的顺序。
+        /// 为[`BitOrder::Lsb0`]时结构体的大小按整字节计算。未指定时继承外层结构，默认为[`BitOrder::Msb0`]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bit_order: Option<BitOrder>,
     },
 
     /// 数组
@@ -303,13 +724,80 @@ pub enum Type {
         size: Option<BytesSize>,
     },
 
+    /// 引用类型
+    ///
+    /// 引用外层[`Type::Struct`]的`definitions`中名为`name`的类型定义，从内到外查找，允许递归引用。
+    /// 类型定义中的引用从该定义所在的位置开始查找。读写时才会解析引用，嵌套层数不能超过[`MAX_REF_DEPTH`]。为避免与[`Field`]的名称冲突，JSON中使用`$ref`表示引用的名称
+    ///
+    /// ```rust
+    /// use bin2json::ty::Field;
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Struct",
+    ///     "definitions": {
+    ///         "point": {
+    ///             "type": "Struct",
+    ///             "fields": [
+    ///                 { "name": "x", "type": "Uint8" },
+    ///                 { "name": "y", "type": "Uint8" }
+    ///             ]
+    ///         }
+    ///     },
+    ///     "fields": [
+    ///         { "name": "start", "type": "Ref", "$ref": "point" }
+    ///     ]
+    /// }"#;
+    /// let point = Type::new_struct(vec![Field::new("x", Type::uint8()), Field::new("y", Type::uint8())]);
+    /// let ty = Type::new_struct(vec![Field::new("start", Type::new_ref("point"))]).with_definition("point", point);
+    /// assert_eq!(ty, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Ref {
+        /// 类型定义的名称
+        #[serde(rename = "$ref")]
+        name: String,
+    },
+
+    /// 指针
+    ///
+    /// 读取偏移量（字节）后，从`base`加上偏移量的位置读取`target`，不改变之后数据的读取位置。
+    /// 写入时，被指向的数据依次放在整个数据流（`base`为[`PointerBase::Buffer`]）或所在结构体（`base`为[`PointerBase::Struct`]）之后，
+    /// 并自动填写偏移量。因此读取结构体时，会跳过结构体之后被`base`为[`PointerBase::Struct`]的指针指向的数据
+    ///
+    /// **注意：** 偏移量的类型必须为大小固定的整数类型
+    ///
+    /// ```rust
+    /// use bin2json::ty::{Endian, PointerBase};
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Pointer",
+    ///     "offset_type": { "type": "Uint32", "unit": { "endian": "Little" } },
+    ///     "target": { "type": "Uint16", "unit": { "endian": "Big" } },
+    ///     "base": "Struct"
+    /// }"#;
+    /// assert_eq!(
+    ///     Type::pointer(Type::uint32(Endian::Little), Type::uint16(Endian::Big), PointerBase::Struct),
+    ///     serde_json::from_str(json)?
+    /// );
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Pointer {
+        /// 偏移量的类型
+        offset_type: Box<Type>,
+        /// 被指向数据的类型
+        target: Box<Type>,
+        /// 偏移量的起始位置
+        #[serde(default)]
+        base: PointerBase,
+    },
+
     /// 枚举
     ///
     /// **注意:**
     /// - 枚举类型不能单独存在，必须位于[`Type::Struct`]的字段列表中
     ///
     /// ```rust
-    /// use bin2json::ty::Endian;
+    /// use bin2json::ty::Unit;
     /// use bin2json::{Type, range_map};
     /// let json = r#"{
     ///     "type": "Enum",
@@ -319,7 +807,7 @@ pub enum Type {
     ///         "2..10": { "type": "Int16" }
     ///     }
     /// }"#;
-    /// assert_eq!(Type::new_enum("field name", range_map!(1 => Type::uint8(), 2..10 => Type::int16(Endian::Big))), serde_json::from_str(json)?);
+    /// assert_eq!(Type::new_enum("field name", range_map!(1 => Type::uint8(), 2..10 => Type::Int16 { unit: Unit::default() })), serde_json::from_str(json)?);
     /// # Ok::<_, serde_json::Error>(())
     /// ```
     Enum {
@@ -332,6 +820,133 @@ pub enum Type {
         size: Option<BytesSize>,
     },
 
+    /// 数值映射
+    ///
+    /// 读取时将整数转换为映射的名称，未找到对应的名称时输出原始数值。写入时可以输入名称或数值
+    ///
+    /// **注意：** 名称只能对应单个数值才能用于写入。被其他字段引用的字段不应使用该类型
+    ///
+    /// ```rust
+    /// use bin2json::{range_map, Type};
+    /// let json = r#"{
+    ///     "type": "Mapping",
+    ///     "original_type": { "type": "Uint8" },
+    ///     "map": {
+    ///         "0": "OK",
+    ///         "3": "TIMEOUT",
+    ///         "10..20": "RESERVED"
+    ///     }
+    /// }"#;
+    /// assert_eq!(
+    ///     Type::mapping(Type::uint8(), range_map!(0 => "OK".to_string(), 3 => "TIMEOUT".to_string(), 10..20 => "RESERVED".to_string())),
+    ///     serde_json::from_str(json)?
+    /// );
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Mapping {
+        /// 原始整数类型
+        original_type: Box<Type>,
+        /// 数值对应的名称
+        map: KeyRangeMap<String>,
+    },
+
+    /// TLV(标签-长度-值)序列
+    ///
+    /// 依次读取标签、长度（值的字节数）和值，直到数据结束。标签在`values`中没有对应的类型时按原始字节读写。
+    /// `names`中定义了名称的标签输出为名称，写入时可以输入名称或数值。写入时自动计算长度
+    ///
+    /// ```rust
+    /// use bin2json::ty::{Endian, TlvFormat};
+    /// use bin2json::{range_map, Type};
+    /// let json = r#"{
+    ///     "type": "Tlv",
+    ///     "tag": { "type": "Uint8" },
+    ///     "length": { "type": "VarInt" },
+    ///     "values": {
+    ///         "1": { "type": "Uint32", "unit": { "endian": "Big" } },
+    ///         "2": { "type": "Uint16", "unit": { "endian": "Little" } }
+    ///     },
+    ///     "names": { "1": "serial", "2": "id" },
+    ///     "format": "Object"
+    /// }"#;
+    /// let mut ty = Type::tlv(
+    ///     Type::uint8(),
+    ///     Type::var_int(false, false),
+    ///     range_map!(1 => Type::uint32(Endian::Big), 2 => Type::uint16(Endian::Little)),
+    /// );
+    /// if let Type::Tlv { names, format, .. } = &mut ty {
+    ///     *names = range_map!(1 => "serial".to_string(), 2 => "id".to_string());
+    ///     *format = TlvFormat::Object;
+    /// }
+    /// assert_eq!(ty, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Tlv {
+        /// 标签的类型，必须为整数类型
+        tag: Box<Type>,
+        /// 长度的类型，必须为整数类型
+        length: Box<Type>,
+        /// 标签对应的值类型
+        values: KeyRangeMap<Type>,
+        /// 标签对应的名称
+        #[serde(default = "tlv::default_names")]
+        names: KeyRangeMap<String>,
+        /// 读取结果的格式
+        #[serde(default)]
+        format: TlvFormat,
+        /// 手动指定TLV序列的总字节大小
+        #[serde(default)]
+        size: Option<BytesSize>,
+    },
+
+    /// 时间戳
+    ///
+    /// 读取时将原始数值转换为RFC 3339格式的时间，写入时可以输入RFC 3339格式的时间或原始数值
+    ///
+    /// ```rust
+    /// use bin2json::ty::{Endian, Epoch, Resolution};
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Timestamp",
+    ///     "original_type": { "type": "Uint64", "unit": { "endian": "Little" } },
+    ///     "epoch": "Windows",
+    ///     "resolution": "HundredNanoseconds",
+    ///     "timezone": "+08:00"
+    /// }"#;
+    /// let ty: Type = serde_json::from_str(json)?;
+    /// if let Type::Timestamp { original_type, epoch, resolution, timezone } = &ty {
+    ///     assert_eq!(&Type::uint64(Endian::Little), original_type.as_ref());
+    ///     assert_eq!(Epoch::Windows, *epoch);
+    ///     assert_eq!(Resolution::HundredNanoseconds, *resolution);
+    ///     assert_eq!(Some(8 * 3600), timezone.map(|tz| tz.local_minus_utc()));
+    /// }
+    ///
+    /// let json = r#"{
+    ///     "type": "Timestamp",
+    ///     "original_type": { "type": "Uint32", "unit": { "endian": "Big" } }
+    /// }"#;
+    /// assert_eq!(
+    ///     Type::timestamp(Type::uint32(Endian::Big), Epoch::Unix, Resolution::Seconds),
+    ///     serde_json::from_str(json)?
+    /// );
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Timestamp {
+        /// 原始类型。可以是整数类型或BCD码
+        original_type: Box<Type>,
+        /// 起始时间
+        #[serde(default)]
+        epoch: Epoch,
+        /// 原始数值的精度
+        #[serde(default)]
+        resolution: Resolution,
+        /// 读取时输出的时区，如：`+08:00`。默认为UTC
+        #[serde(default)]
+        #[serde(serialize_with = "timestamp::se_timezone")]
+        #[serde(deserialize_with = "timestamp::de_timezone")]
+        timezone: Option<FixedOffset>,
+    },
+
     /// 转换
     ///
     /// 可以在执行[`Type::convert`]时对数据进行额外的计算，支持的表达式见[expreval](https://docs.rs/evalexpr/latest/evalexpr/)
@@ -351,6 +966,28 @@ pub enum Type {
         on_write: Converter,
     },
 
+    /// 计算字段
+    ///
+    /// 不占用任何数据，只存在于JSON值中。读取时执行表达式，并将结果作为本字段的值；写入时忽略本字段。
+    /// 支持的表达式见[expreval](https://docs.rs/evalexpr/latest/evalexpr/)
+    ///
+    /// 执行表达式时可以使用同级且在本字段之前的字段的值，变量名为`self.field_name`。
+    /// 其中整数为整型变量，只由整数计算的结果仍为整数（除法结果向零取整），如需小数结果可乘以`1.0`
+    ///
+    /// ```rust
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Computed",
+    ///     "expr": "self.hi * 256 + self.lo"
+    /// }"#;
+    /// assert_eq!(Type::computed("self.hi * 256 + self.lo"), serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Computed {
+        /// 计算本字段值的表达式
+        expr: String,
+    },
+
     /// 校验和
     ///
     /// **注意：** 该类型必须定义于结构体之中
@@ -380,6 +1017,40 @@ pub enum Type {
         size: Option<BytesSize>,
     },
 
+    /// 压缩数据
+    ///
+    /// 读取时先按`size`取出数据并解压，再从解压后的数据中读取`inner_type`；写入时压缩`inner_type`的写入结果。
+    /// 解压后的数据不能超过[`Options::max_decompressed_size`]
+    ///
+    /// **注意:** 当本类型作为[`Type::Struct`]的一个字段时:
+    /// - `inner_type`视为与结构体的其他字段同级, 但**不包括**[`Type::Checksum`]
+    ///
+    /// ```rust
+    /// use bin2json::ty::{BytesSize, Compression};
+    /// use bin2json::Type;
+    /// let json = r#"{
+    ///     "type": "Compressed",
+    ///     "algorithm": "Zlib",
+    ///     "inner_type": { "type": "Bin" },
+    ///     "size": "len"
+    /// }"#;
+    /// let ty = Type::Compressed {
+    ///     algorithm: Compression::Zlib,
+    ///     inner_type: Box::new(Type::Bin { size: None, padding: None }),
+    ///     size: Some(BytesSize::new("len")),
+    /// };
+    /// assert_eq!(ty, serde_json::from_str(json)?);
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    Compressed {
+        /// 压缩算法
+        algorithm: Compression,
+        /// 解压后数据的解析类型
+        inner_type: Box<Type>,
+        #[serde(default)]
+        size: Option<BytesSize>,
+    },
+
     /// 数据签名
     ///
     /// **注意：** 该类型必须定义于结构体之中
@@ -413,6 +1084,17 @@ impl Type {
         }
     }
 
+    pub fn padding(size: BitSize) -> Self {
+        Self::Padding { size, fill: vec![] }
+    }
+
+    pub fn align(align: BitSize) -> Self {
+        Self::Align {
+            align,
+            fill: vec![],
+        }
+    }
+
     pub fn int8() -> Self {
         Self::Int8 {
             unit: Default::default(),
@@ -437,6 +1119,12 @@ impl Type {
         }
     }
 
+    pub fn int128(endian: Endian) -> Self {
+        Self::Int128 {
+            unit: endian.into(),
+        }
+    }
+
     pub fn uint8() -> Self {
         Self::Uint8 {
             unit: Default::default(),
@@ -461,30 +1149,170 @@ impl Type {
         }
     }
 
+    pub fn uint128(endian: Endian) -> Self {
+        Self::Uint128 {
+            unit: endian.into(),
+        }
+    }
+
+    pub fn var_int(signed: bool, zigzag: bool) -> Self {
+        Self::VarInt { signed, zigzag }
+    }
+
+    pub fn float16(endian: Endian) -> Self {
+        Self::Float16 {
+            endian: Some(endian),
+        }
+    }
+
+    pub fn bfloat16(endian: Endian) -> Self {
+        Self::BFloat16 {
+            endian: Some(endian),
+        }
+    }
+
     pub fn float32(endian: Endian) -> Self {
-        Self::Float32 { endian }
+        Self::Float32 {
+            endian: Some(endian),
+        }
     }
 
     pub fn float64(endian: Endian) -> Self {
-        Self::Float64 { endian }
+        Self::Float64 {
+            endian: Some(endian),
+        }
+    }
+
+    pub fn fixed(signed: bool, integer_bits: usize, fraction_bits: usize, endian: Endian) -> Self {
+        Self::Fixed {
+            signed,
+            integer_bits,
+            fraction_bits,
+            unit: endian.into(),
+            rounding: Default::default(),
+        }
+    }
+
+    pub fn bcd(digits: usize, endian: Endian) -> Self {
+        Self::Bcd {
+            digits,
+            unpacked: false,
+            signed: false,
+            endian: Some(endian),
+            as_string: false,
+        }
+    }
+
+    pub fn flags<I, S>(unit: Unit, flags: I) -> Self
+    where
+        I: IntoIterator<Item = (u8, S)>,
+        S: Into<String>,
+    {
+        Self::Flags {
+            unit,
+            flags: flags.into_iter().map(|(b, n)| (b, n.into())).collect(),
+            format: FlagsFormat::List,
+        }
+    }
+
+    pub fn uuid(endian: Endian) -> Self {
+        Self::Uuid { endian }
     }
 
     pub fn string(size: BytesSize) -> Self {
-        Self::String { size: Some(size) }
+        Self::String {
+            size: Some(size),
+            encoding: Encoding::Utf8,
+            lossy: false,
+            padding: None,
+        }
+    }
+
+    pub fn string_with_encoding(size: BytesSize, encoding: Encoding) -> Self {
+        Self::String {
+            size: Some(size),
+            encoding,
+            lossy: false,
+            padding: None,
+        }
+    }
+
+    pub fn padded_string(size: usize, padding: u8) -> Self {
+        Self::String {
+            size: Some(BytesSize::Fixed(size)),
+            encoding: Encoding::Utf8,
+            lossy: false,
+            padding: Some(padding),
+        }
     }
 
     pub fn bin(size: BytesSize) -> Self {
-        Self::Bin { size: Some(size) }
+        Self::Bin {
+            size: Some(size),
+            padding: None,
+        }
+    }
+
+    pub fn padded_bin(size: usize, padding: u8) -> Self {
+        Self::Bin {
+            size: Some(BytesSize::Fixed(size)),
+            padding: Some(padding),
+        }
     }
 
     pub fn new_struct(fields: Vec<Field>) -> Self {
-        Self::Struct { fields, size: None }
+        Self::Struct {
+            fields,
+            size: None,
+            definitions: BTreeMap::new(),
+            default_endian: None,
+            bit_order: None,
+        }
     }
 
     pub fn new_struct_with_size(fields: Vec<Field>, size: BytesSize) -> Self {
         Self::Struct {
             fields,
             size: Some(size),
+            definitions: BTreeMap::new(),
+            default_endian: None,
+            bit_order: None,
+        }
+    }
+
+    /// 为结构体添加命名的类型定义。如果本类型不是[`Type::Struct`]则忽略
+    pub fn with_definition<S: Into<String>>(mut self, name: S, ty: Type) -> Self {
+        if let Self::Struct { definitions, .. } = &mut self {
+            definitions.insert(name.into(), ty);
+        }
+        self
+    }
+
+    /// 设置结构体中按位排列的字段在字节内的顺序。如果本类型不是[`Type::Struct`]则忽略
+    pub fn with_bit_order(mut self, order: BitOrder) -> Self {
+        if let Self::Struct { bit_order, .. } = &mut self {
+            *bit_order = Some(order);
+        }
+        self
+    }
+
+    /// 设置结构体中未指定字节顺序的字段使用的字节顺序。如果本类型不是[`Type::Struct`]则忽略
+    pub fn with_default_endian(mut self, endian: Endian) -> Self {
+        if let Self::Struct { default_endian, .. } = &mut self {
+            *default_endian = Some(endian);
+        }
+        self
+    }
+
+    pub fn new_ref<S: Into<String>>(name: S) -> Self {
+        Self::Ref { name: name.into() }
+    }
+
+    pub fn pointer(offset_type: Type, target: Type, base: PointerBase) -> Self {
+        Self::Pointer {
+            offset_type: Box::new(offset_type),
+            target: Box::new(target),
+            base,
         }
     }
 
@@ -520,6 +1348,33 @@ impl Type {
         }
     }
 
+    pub fn mapping<M: Into<KeyRangeMap<String>>>(ty: Type, map: M) -> Self {
+        Self::Mapping {
+            original_type: Box::new(ty),
+            map: map.into(),
+        }
+    }
+
+    pub fn tlv<M: Into<KeyRangeMap<Type>>>(tag: Type, length: Type, values: M) -> Self {
+        Self::Tlv {
+            tag: Box::new(tag),
+            length: Box::new(length),
+            values: values.into(),
+            names: KeyRangeMap::new(),
+            format: TlvFormat::List,
+            size: None,
+        }
+    }
+
+    pub fn timestamp(ty: Type, epoch: Epoch, resolution: Resolution) -> Self {
+        Self::Timestamp {
+            original_type: Box::new(ty),
+            epoch,
+            resolution,
+            timezone: None,
+        }
+    }
+
     pub fn converter<S: Into<String>>(ty: Type, on_read: S, on_write: S) -> Self {
         Self::Converter {
             original_type: Box::new(ty),
@@ -528,6 +1383,10 @@ impl Type {
         }
     }
 
+    pub fn computed<S: Into<String>>(expr: S) -> Self {
+        Self::Computed { expr: expr.into() }
+    }
+
     pub fn checksum<S: Into<String>>(method: Checksum, start_key: S) -> Self {
         Self::Checksum {
             method,
@@ -545,6 +1404,14 @@ impl Type {
         }
     }
 
+    pub fn compressed(ty: Type, algorithm: Compression) -> Self {
+        Self::Compressed {
+            algorithm,
+            inner_type: Box::new(ty),
+            size: None,
+        }
+    }
+
     pub fn sign<S: Into<String>>(start_key: S, on_read: SecureKey, on_write: SecureKey) -> Self {
         Self::Sign {
             on_read,
@@ -558,34 +1425,55 @@ impl Type {
     pub const fn type_name(&self) -> &'static str {
         match self {
             Type::Magic { .. } => "Magic",
+            Type::Padding { .. } => "Padding",
+            Type::Align { .. } => "Align",
             Type::Boolean { .. } => "Boolean",
             Type::Int8 { .. } => "Int8",
             Type::Int16 { .. } => "Int16",
             Type::Int32 { .. } => "Int32",
             Type::Int64 { .. } => "Int64",
+            Type::Int128 { .. } => "Int128",
             Type::Uint8 { .. } => "Uint8",
             Type::Uint16 { .. } => "Uint16",
             Type::Uint32 { .. } => "Uint32",
             Type::Uint64 { .. } => "Uint64",
+            Type::Uint128 { .. } => "Uint128",
+            Type::VarInt { .. } => "VarInt",
+            Type::Float16 { .. } => "Float16",
+            Type::BFloat16 { .. } => "BFloat16",
             Type::Float32 { .. } => "Float32",
             Type::Float64 { .. } => "Float64",
+            Type::Fixed { .. } => "Fixed",
+            Type::Bcd { .. } => "Bcd",
+            Type::Flags { .. } => "Flags",
+            Type::Ipv4 => "IPv4",
+            Type::Ipv6 => "IPv6",
+            Type::Mac => "MAC",
+            Type::Uuid { .. } => "UUID",
             Type::String { .. } => "String",
             Type::Bin { .. } => "Bin",
             Type::Struct { .. } => "Struct",
             Type::Array { .. } => "Array",
+            Type::Ref { .. } => "Ref",
+            Type::Pointer { .. } => "Pointer",
             Type::Enum { .. } => "Enum",
+            Type::Mapping { .. } => "Mapping",
+            Type::Tlv { .. } => "Tlv",
+            Type::Timestamp { .. } => "Timestamp",
             Type::Converter { .. } => "Converter",
+            Type::Computed { .. } => "Computed",
             Type::Checksum { .. } => "Checksum",
             Type::Encrypt { .. } => "Encrypt",
+            Type::Compressed { .. } => "Compressed",
             Type::Sign { .. } => "Sign",
         }
     }
 }
 
 macro_rules! parse_numeric_field {
-    ($input: expr, $name: expr, $ty: ty, $unit: expr) => {{
+    ($input: expr, $name: expr, $ty: ty, $unit: expr, $ctx: expr) => {{
         let size = $unit.size.unwrap_or(BitSize::of::<$ty>());
-        let (input, value) = <$ty>::read($input, (($unit.endian).into(), size))?;
+        let (input, value) = <$ty>::read($input, ($ctx.endian($unit).into(), size))?;
         (value.into(), input)
     }};
 }
@@ -598,6 +1486,23 @@ impl Type {
     pub fn read<'a>(
         &self,
         data: &'a BitSlice<u8, Msb0>,
+    ) -> Result<(Value, &'a BitSlice<u8, Msb0>), ReadBinError> {
+        self.read_with(data, &Options::default())
+    }
+
+    /// 使用指定的选项读取，见[`Type::read`]
+    pub fn read_with<'a>(
+        &self,
+        data: &'a BitSlice<u8, Msb0>,
+        options: &Options,
+    ) -> Result<(Value, &'a BitSlice<u8, Msb0>), ReadBinError> {
+        self.read_ctx(data, &Context::with_options(options).with_buffer(data))
+    }
+
+    pub(crate) fn read_ctx<'a>(
+        &self,
+        data: &'a BitSlice<u8, Msb0>,
+        ctx: &Context,
     ) -> Result<(Value, &'a BitSlice<u8, Msb0>), ReadBinError> {
         let (value, data): (Value, _) = match self {
             Self::Magic { ref magic } => {
@@ -610,6 +1515,14 @@ impl Type {
                     return Err(ReadBinError::MagicError(magic.clone()));
                 }
             }
+            Self::Padding { size, .. } => {
+                if data.len() < size.0 {
+                    return Err(ReadBinError::Incomplete);
+                }
+                (Value::Null, &data[size.0..])
+            }
+            // 单独读取时起始位置总是对齐的
+            Self::Align { .. } => (Value::Null, data),
             Self::Boolean { bit } => {
                 let size = if *bit {
                     BitSize(1)
@@ -620,62 +1533,190 @@ impl Type {
                 (v.into(), input)
             }
             Self::Int8 { unit } => {
-                parse_numeric_field!(data, field.name, i8, unit)
+                parse_numeric_field!(data, field.name, i8, unit, ctx)
             }
             Self::Int16 { unit } => {
-                parse_numeric_field!(data, field.name, i16, unit)
+                parse_numeric_field!(data, field.name, i16, unit, ctx)
             }
             Self::Int32 { unit } => {
-                parse_numeric_field!(data, field.name, i32, unit)
+                parse_numeric_field!(data, field.name, i32, unit, ctx)
             }
             Self::Int64 { unit } => {
-                parse_numeric_field!(data, field.name, i64, unit)
+                parse_numeric_field!(data, field.name, i64, unit, ctx)
+            }
+            Self::Int128 { unit } => {
+                let (v, input): (i128, _) = parse_numeric_field!(data, field.name, i128, unit, ctx);
+                (v.to_string().into(), input)
             }
             Self::Uint8 { unit } => {
-                parse_numeric_field!(data, field.name, u8, unit)
+                parse_numeric_field!(data, field.name, u8, unit, ctx)
             }
             Self::Uint16 { unit } => {
-                parse_numeric_field!(data, field.name, u16, unit)
+                parse_numeric_field!(data, field.name, u16, unit, ctx)
             }
             Self::Uint32 { unit } => {
-                parse_numeric_field!(data, field.name, u32, unit)
+                parse_numeric_field!(data, field.name, u32, unit, ctx)
             }
             Self::Uint64 { unit } => {
-                parse_numeric_field!(data, field.name, u64, unit)
+                parse_numeric_field!(data, field.name, u64, unit, ctx)
+            }
+            Self::Uint128 { unit } => {
+                let (v, input): (u128, _) = parse_numeric_field!(data, field.name, u128, unit, ctx);
+                (v.to_string().into(), input)
+            }
+            Self::VarInt { signed, zigzag } => read_var_int(data, *signed, *zigzag)?,
+            Self::Float16 { endian } => {
+                let (input, v): (_, u16) =
+                    DekuRead::<'_, deku::ctx::Endian>::read(data, ctx.endian_or(*endian).into())?;
+                (f16::from_bits(v).to_f64().into(), input)
+            }
+            Self::BFloat16 { endian } => {
+                let (input, v): (_, u16) =
+                    DekuRead::<'_, deku::ctx::Endian>::read(data, ctx.endian_or(*endian).into())?;
+                (bf16::from_bits(v).to_f64().into(), input)
             }
             Self::Float32 { endian } => {
                 let (input, v): (_, f32) =
-                    DekuRead::<'_, deku::ctx::Endian>::read(data, (*endian).into())?;
+                    DekuRead::<'_, deku::ctx::Endian>::read(data, ctx.endian_or(*endian).into())?;
                 (v.into(), input)
             }
             Self::Float64 { endian } => {
                 let (input, v): (_, f64) =
-                    DekuRead::<'_, deku::ctx::Endian>::read(data, (*endian).into())?;
+                    DekuRead::<'_, deku::ctx::Endian>::read(data, ctx.endian_or(*endian).into())?;
                 (v.into(), input)
             }
-            Self::String { ref size } | Type::Bin { ref size } => {
+            Self::Fixed {
+                signed,
+                integer_bits,
+                fraction_bits,
+                unit,
+                ..
+            } => {
+                let size = unit.size.unwrap_or(BitSize(integer_bits + fraction_bits));
+                let deku_ctx: (deku::ctx::Endian, BitSize) = (ctx.endian(unit).into(), size);
+                let (input, raw) = if *signed {
+                    let (input, v) = i64::read(data, deku_ctx)?;
+                    (input, v as f64)
+                } else {
+                    let (input, v) = u64::read(data, deku_ctx)?;
+                    (input, v as f64)
+                };
+                ((raw / 2f64.powi(*fraction_bits as i32)).into(), input)
+            }
+            Self::Bcd {
+                digits,
+                unpacked,
+                signed,
+                endian,
+                as_string,
+            } => read_bcd(
+                data,
+                *digits,
+                *unpacked,
+                *signed,
+                ctx.endian_or(*endian),
+                *as_string,
+            )?,
+            Self::Flags {
+                unit,
+                flags,
+                format,
+            } => read_flags(data, unit, ctx.endian(unit), flags, *format)?,
+            Self::Ipv4 => read_address(data, Address::Ipv4)?,
+            Self::Ipv6 => read_address(data, Address::Ipv6)?,
+            Self::Mac => read_address(data, Address::Mac)?,
+            Self::Uuid { endian } => read_address(data, Address::Uuid(*endian))?,
+            Self::String {
+                ref size,
+                ref padding,
+                ..
+            }
+            | Type::Bin {
+                ref size,
+                ref padding,
+            } => {
                 let d = get_data_by_size(data, size, None)?;
                 let d_len = d.len();
 
-                let (_, v) = Vec::<u8>::read(data, Limit::new_bit_size(BitSize(d_len)))?;
-                let v = if let Type::String { .. } = self {
-                    String::from_utf8(v)?.into()
+                let (_, mut v) = Vec::<u8>::read(data, Limit::new_bit_size(BitSize(d_len)))?;
+                if let Some(padding) = padding {
+                    let len = v.iter().rposition(|b| b != padding).map_or(0, |i| i + 1);
+                    // 避免截断多字节编码的最后一个编码单元
+                    let len = if let Type::String { encoding, .. } = self {
+                        len.next_multiple_of(encoding.unit_size()).min(v.len())
+                    } else {
+                        len
+                    };
+                    v.truncate(len);
+                }
+                let v = if let Type::String {
+                    encoding, lossy, ..
+                } = self
+                {
+                    encoding.decode(&v, *lossy)?.into()
                 } else {
                     v.into()
                 };
                 (v, &data[d_len..])
             }
-            Self::Struct { fields, size } => read_struct(fields, size, data)?,
+            Self::Struct {
+                fields,
+                size,
+                definitions,
+                default_endian,
+                bit_order,
+            } => ctx.with_definitions(definitions, |ctx| {
+                read_struct(fields, size, data, &ctx.with_defaults(*default_endian, *bit_order))
+            })?,
             Self::Array {
                 element_type: ty,
                 size,
                 length,
-            } => read_array(ty, length, size, data)?,
+            } => read_array(ty, length, size, data, ctx)?,
+            Self::Ref { name } => {
+                let (ty, ctx) = ctx.enter(name)?;
+                ty.read_ctx(data, &ctx)?
+            }
+            Self::Pointer {
+                offset_type,
+                target,
+                base,
+            } => read_pointer(data, offset_type, target, *base, ctx)?,
+            Self::Mapping { original_type, map } => {
+                let (value, d) = original_type.read_ctx(data, ctx)?;
+                let value = utils::as_i64(&value)
+                    .and_then(|key| map.get(&key))
+                    .map_or(value, |name| name.clone().into());
+                (value, d)
+            }
+            Self::Tlv {
+                tag,
+                length,
+                values,
+                names,
+                format,
+                size,
+            } => {
+                let d = get_data_by_size(data, size, None)?;
+                let (value, _) = read_tlv(d, tag, length, values, names, *format, ctx)?;
+                (value, &data[d.len()..])
+            }
+            Self::Timestamp {
+                original_type,
+                epoch,
+                resolution,
+                timezone,
+            } => {
+                let (value, d) = original_type.read_ctx(data, ctx)?;
+                (to_rfc3339(&value, epoch, *resolution, *timezone)?, d)
+            }
             Self::Converter { original_type, .. } => {
-                let (value, d) = original_type.read(data)?;
+                let (value, d) = original_type.read_ctx(data, ctx)?;
                 let value = self.convert(&value, true)?;
                 (value, d)
             }
+            // 单独读取时没有同级字段
+            Self::Computed { expr } => (utils::to_json_value(evalexpr::eval(expr)?), data),
 
             Self::Encrypt {
                 inner_type,
@@ -685,9 +1726,19 @@ impl Type {
             } => {
                 let en_data = get_data_by_size(data, size, None)?;
                 let de_data = on_read.decrypt(en_data)?;
-                let (v, _) = inner_type.read(&de_data)?;
+                let (v, _) = inner_type.read_ctx(&de_data, ctx)?;
                 (v, &data[en_data.len()..])
             }
+            Self::Compressed {
+                algorithm,
+                inner_type,
+                size,
+            } => {
+                let compressed = get_data_by_size(data, size, None)?;
+                let de_data = algorithm.decompress(compressed, ctx.max_decompressed_size())?;
+                let (v, _) = inner_type.read_ctx(&de_data, ctx)?;
+                (v, &data[compressed.len()..])
+            }
 
             Self::Enum { by, .. }
             | Self::Checksum { start_key: by, .. }
@@ -705,6 +1756,24 @@ impl Type {
     ///
     /// **注意:** 调用之前应对调用[`Type::convert`]方法转换数据，本方法不会对[`Type::Converter`]中的数据进行转化
     pub fn write(&self, value: &serde_json::Value) -> Result<BitVec<u8, Msb0>, WriteBinError> {
+        self.write_with(value, &Options::default())
+    }
+
+    /// 使用指定的选项写入，见[`Type::write`]
+    pub fn write_with(
+        &self,
+        value: &serde_json::Value,
+        options: &Options,
+    ) -> Result<BitVec<u8, Msb0>, WriteBinError> {
+        Context::with_options(options)
+            .write_pointed(PointerBase::Buffer, |ctx| self.write_ctx(value, ctx))
+    }
+
+    pub(crate) fn write_ctx(
+        &self,
+        value: &serde_json::Value,
+        ctx: &Context,
+    ) -> Result<BitVec<u8, Msb0>, WriteBinError> {
         let mut output = BitVec::new();
 
         macro_rules! v {
@@ -712,27 +1781,63 @@ impl Type {
                 $conv.ok_or(WriteBinError::TypeError(self.type_name()))?
             }};
         }
+        macro_rules! num {
+            ($need_ty: ty) => {{
+                // 整数直接转换，避免超过2^53的整数经过浮点数转换后丢失精度
+                if let Some(v) = value.as_u64() {
+                    <$need_ty>::try_from(v)
+                        .map_err(|_| WriteBinError::ValueOverflowOf(self.type_name()))?
+                } else if let Some(v) = value.as_i64() {
+                    <$need_ty>::try_from(v)
+                        .map_err(|_| WriteBinError::ValueOverflowOf(self.type_name()))?
+                } else {
+                    let v = v!(value.as_f64());
+                    if v >= <$need_ty>::MIN as f64 && v <= <$need_ty>::MAX as f64 {
+                        v as $need_ty
+                    } else {
+                        return Err(WriteBinError::ValueOverflowOf(self.type_name()));
+                    }
+                }
+            }};
+        }
         macro_rules! write_num {
             ($need_ty: ty, $unit: ident) => {
-                let v = v!(value.as_f64());
-                if v >= <$need_ty>::MIN as f64 && v <= <$need_ty>::MAX as f64 {
-                    let ctx: (deku::ctx::Endian, BitSize) = (
-                        $unit.endian.into(),
-                        $unit.size.unwrap_or(BitSize::of::<$need_ty>()),
-                    );
-                    (v as $need_ty).write(&mut output, ctx)?;
+                let v = num!($need_ty);
+                let ctx: (deku::ctx::Endian, BitSize) = (
+                    ctx.endian($unit).into(),
+                    $unit.size.unwrap_or(BitSize::of::<$need_ty>()),
+                );
+                v.write(&mut output, ctx)?;
+            };
+        }
+        macro_rules! write_num128 {
+            ($need_ty: ty, $unit: ident) => {
+                let v = if let Some(s) = value.as_str() {
+                    s.trim().parse::<$need_ty>().map_err(|e| match e.kind() {
+                        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                            WriteBinError::ValueOverflowOf(self.type_name())
+                        }
+                        _ => WriteBinError::TypeError(self.type_name()),
+                    })?
                 } else {
-                    return Err(WriteBinError::ValueOverflowOf(self.type_name()));
-                }
+                    num!($need_ty)
+                };
+                let ctx: (deku::ctx::Endian, BitSize) = (
+                    ctx.endian($unit).into(),
+                    $unit.size.unwrap_or(BitSize::of::<$need_ty>()),
+                );
+                v.write(&mut output, ctx)?;
             };
         }
 
         match self {
             Type::String {
                 size: Some(BytesSize::By(_) | BytesSize::Enum { .. }),
+                ..
             }
             | Type::Bin {
                 size: Some(BytesSize::By(_) | BytesSize::Enum { .. }),
+                ..
             }
             | Type::Struct {
                 size: Some(BytesSize::By(_) | BytesSize::Enum { .. }),
@@ -746,15 +1851,26 @@ impl Type {
                 length: Some(Length::By(_)),
                 ..
             }
+            | Type::Tlv {
+                size: Some(BytesSize::By(_) | BytesSize::Enum { .. }),
+                ..
+            }
             | Type::Enum { .. }
             | Type::Checksum { .. }
             | Type::Encrypt {
                 size: Some(BytesSize::By(_) | BytesSize::Enum { .. }),
                 ..
             }
+            | Type::Compressed {
+                size: Some(BytesSize::By(_) | BytesSize::Enum { .. }),
+                ..
+            }
             | Type::Sign { .. } => return Err(WriteBinError::ByError),
 
             Type::Magic { magic } => magic.write(&mut output, ())?,
+            Type::Padding { size, fill } => output = utils::fill_bits(fill, size.0),
+            // 单独写入时起始位置总是对齐的
+            Type::Align { .. } => {}
             Type::Boolean { bit } => {
                 let b = v!(value.as_bool());
                 let size = if *bit {
@@ -776,6 +1892,9 @@ impl Type {
             Type::Int64 { unit } => {
                 write_num!(i64, unit);
             }
+            Type::Int128 { unit } => {
+                write_num128!(i128, unit);
+            }
             Type::Uint8 { unit } => {
                 write_num!(u8, unit);
             }
@@ -788,30 +1907,111 @@ impl Type {
             Type::Uint64 { unit } => {
                 write_num!(u64, unit);
             }
+            Type::Uint128 { unit } => {
+                write_num128!(u128, unit);
+            }
+            Type::VarInt { signed, zigzag } => {
+                write_var_int(value, *signed, *zigzag, &mut output)?;
+            }
+            Type::Float16 { endian } => {
+                let v = v!(value.as_f64());
+                let f = f16::from_f64(v);
+                if f.is_infinite() && !v.is_infinite() {
+                    return Err(WriteBinError::ValueOverflowOf(self.type_name()));
+                } else {
+                    let endian: deku::ctx::Endian = ctx.endian_or(*endian).into();
+                    f.to_bits().write(&mut output, endian)?;
+                }
+            }
+            Type::BFloat16 { endian } => {
+                let v = v!(value.as_f64());
+                let f = bf16::from_f64(v);
+                if f.is_infinite() && !v.is_infinite() {
+                    return Err(WriteBinError::ValueOverflowOf(self.type_name()));
+                } else {
+                    let endian: deku::ctx::Endian = ctx.endian_or(*endian).into();
+                    f.to_bits().write(&mut output, endian)?;
+                }
+            }
             Type::Float32 { endian } => {
                 let v = v!(value.as_f64());
                 let f = v as f32;
                 if f.is_infinite() && !v.is_infinite() {
                     return Err(WriteBinError::ValueOverflowOf(self.type_name()));
                 } else {
-                    let endian: deku::ctx::Endian = (*endian).into();
+                    let endian: deku::ctx::Endian = ctx.endian_or(*endian).into();
                     f.write(&mut output, endian)?;
                 }
             }
             Type::Float64 { endian } => {
-                let endian: deku::ctx::Endian = (*endian).into();
+                let endian: deku::ctx::Endian = ctx.endian_or(*endian).into();
                 v!(value.as_f64()).write(&mut output, endian)?;
             }
-            Type::Bin { size } | Type::String { size } => {
-                #[allow(unused_assignments)]
-                let mut c = None;
-
-                let b = if let Type::String { .. } = self {
-                    v!(value.as_str()).as_bytes()
+            Type::Fixed {
+                signed,
+                integer_bits,
+                fraction_bits,
+                unit,
+                rounding,
+            } => {
+                let size = unit.size.unwrap_or(BitSize(integer_bits + fraction_bits));
+                if size.0 == 0 || size.0 > 64 {
+                    return Err(WriteBinError::ValueOverflowOf(self.type_name()));
+                }
+                let deku_ctx: (deku::ctx::Endian, BitSize) = (ctx.endian(unit).into(), size);
+                let raw = rounding.round(v!(value.as_f64()) * 2f64.powi(*fraction_bits as i32));
+                // 上限使用不包含的2的幂，避免2^63 - 1等值转换为浮点数时进位
+                let (min, max) = if *signed {
+                    (-(2f64.powi(size.0 as i32 - 1)), 2f64.powi(size.0 as i32 - 1))
+                } else {
+                    (0.0, 2f64.powi(size.0 as i32))
+                };
+                if !raw.is_finite() || raw < min || raw >= max {
+                    return Err(WriteBinError::ValueOverflowOf(self.type_name()));
+                }
+                if *signed {
+                    (raw as i64).write(&mut output, deku_ctx)?;
+                } else {
+                    (raw as u64).write(&mut output, deku_ctx)?;
+                }
+            }
+            Type::Bcd {
+                digits,
+                unpacked,
+                signed,
+                endian,
+                ..
+            } => {
+                write_bcd(
+                    value,
+                    *digits,
+                    *unpacked,
+                    *signed,
+                    ctx.endian_or(*endian),
+                    &mut output,
+                )?;
+            }
+            Type::Flags { unit, flags, .. } => {
+                write_flags(value, unit, ctx.endian(unit), flags, &mut output)?;
+            }
+            Type::Ipv4 => write_address(value, Address::Ipv4, &mut output)?,
+            Type::Ipv6 => write_address(value, Address::Ipv6, &mut output)?,
+            Type::Mac => write_address(value, Address::Mac, &mut output)?,
+            Type::Uuid { endian } => write_address(value, Address::Uuid(*endian), &mut output)?,
+            Type::Bin { size, padding } | Type::String { size, padding, .. } => {
+                let mut b = if let Type::String {
+                    encoding, lossy, ..
+                } = self
+                {
+                    encoding.encode(v!(value.as_str()), *lossy)?
                 } else {
-                    c = Some(utils::get_bin(v!(value.as_array()), self.type_name())?);
-                    c.as_ref().map(|v| v.as_slice()).unwrap()
+                    utils::get_bin(v!(value.as_array()), self.type_name())?
                 };
+                if let (Some(BytesSize::Fixed(size)), Some(padding)) = (size, padding) {
+                    if b.len() < *size {
+                        b.resize(*size, *padding);
+                    }
+                }
 
                 let e = match size {
                     Some(BytesSize::Fixed(size)) => *size == b.len(),
@@ -832,10 +2032,39 @@ impl Type {
             } => {
                 let mut out = BitVec::new();
                 let mut len = 0;
-                v!(value.as_array())
+                let mut array = v!(value.as_array()).as_slice();
+                let mut terminator = None;
+                if let Some(Length::Until { until, consume, .. }) = length {
+                    let terminated = match array.last() {
+                        Some(last) => until.matches(last)?,
+                        None => false,
+                    };
+                    for v in array.iter().take(array.len().saturating_sub(1)) {
+                        if until.matches(v)? {
+                            return Err(WriteBinError::TerminatorError("终止成员只能位于数组末尾"));
+                        }
+                    }
+
+                    if !*consume {
+                        if terminated {
+                            array = &array[..array.len() - 1];
+                        }
+                    } else if !terminated {
+                        match until {
+                            Terminator::Value(v) => terminator = Some(v),
+                            Terminator::Expr(_) => {
+                                return Err(WriteBinError::TerminatorError(
+                                    "使用表达式作为终止条件时，数组最后一个成员必须满足终止条件",
+                                ))
+                            }
+                        }
+                    }
+                }
+                array
                     .iter()
+                    .chain(terminator)
                     .map(|v| -> Result<(), WriteBinError> {
-                        out.append(&mut element_type.write(v)?);
+                        out.append(&mut element_type.write_ctx(v, ctx)?);
                         len += 1;
                         Ok(())
                     })
@@ -853,17 +2082,69 @@ impl Type {
                 utils::check_size(size, &out)?;
                 output = out;
             }
-            Type::Struct { fields, size } => {
+            Type::Struct {
+                fields,
+                size,
+                definitions,
+                default_endian,
+                bit_order,
+            } => {
                 let obj = v!(value.as_object());
-                let out = write_struct(fields, obj)?;
+                let out = ctx.with_definitions(definitions, |ctx| {
+                    ctx.with_defaults(*default_endian, *bit_order)
+                        .write_pointed(PointerBase::Struct, |ctx| write_struct(fields, obj, ctx))
+                })?;
                 utils::check_size(size, &out)?;
                 output = out;
             }
+            Type::Ref { name } => {
+                let (ty, ctx) = ctx.enter(name)?;
+                output = ty.write_ctx(value, &ctx)?;
+            }
+            Type::Pointer {
+                offset_type,
+                target,
+                base,
+            } => output = write_pointer(value, offset_type, target, *base, ctx)?,
+
+            Type::Mapping { original_type, map } => {
+                output = if let Value::String(name) = value {
+                    match map.find_key(name) {
+                        Some(KeyRange::Value(key)) => original_type.write_ctx(&key.into(), ctx)?,
+                        _ => return Err(WriteBinError::MappingError(name.clone())),
+                    }
+                } else {
+                    original_type.write_ctx(value, ctx)?
+                };
+            }
+
+            Type::Tlv {
+                tag,
+                length,
+                values,
+                names,
+                size,
+                ..
+            } => {
+                output = write_tlv(value, tag, length, values, names, ctx)?;
+                utils::check_size(size, &output)?;
+            }
+
+            Type::Timestamp {
+                original_type,
+                epoch,
+                resolution,
+                ..
+            } => {
+                let value = from_rfc3339(value, epoch, *resolution)?;
+                output = original_type.write_ctx(&value, ctx)?;
+            }
 
             Type::Converter { original_type, .. } => {
                 let value = self.convert(value, false)?;
-                output = original_type.write(&value)?;
+                output = original_type.write_ctx(&value, ctx)?;
             }
+            Type::Computed { .. } => {}
 
             Type::Encrypt {
                 inner_type,
@@ -871,11 +2152,21 @@ impl Type {
                 size,
                 ..
             } => {
-                let data = inner_type.write(value)?;
+                let data = inner_type.write_ctx(value, ctx)?;
                 let data = on_write.encrypt(data)?;
                 utils::check_size(size, &data)?;
                 output = data;
             }
+            Type::Compressed {
+                algorithm,
+                inner_type,
+                size,
+            } => {
+                let data = inner_type.write_ctx(value, ctx)?;
+                let data = algorithm.compress(data)?;
+                utils::check_size(size, &data)?;
+                output = data;
+            }
         };
 
         Ok(output)
@@ -886,6 +2177,15 @@ impl Type {
 impl Type {
     /// 如果类型为[`Type::Converter`]，则将输入值作为变量执行设置的表达式，并返回表达式执行的结果，否则返回输入值
     pub fn convert(&self, value: &Value, is_read: bool) -> Result<Value, evalexpr::EvalexprError> {
+        self.convert_ctx(value, is_read, &Context::default())
+    }
+
+    pub(crate) fn convert_ctx(
+        &self,
+        value: &Value,
+        is_read: bool,
+        ctx: &Context,
+    ) -> Result<Value, evalexpr::EvalexprError> {
         let value = value.clone();
         match (self, value) {
             (
@@ -900,75 +2200,98 @@ impl Type {
                     on_write.convert(value)
                 }
             }
-            (Type::Struct { fields, .. }, Value::Object(mut map)) => {
-                fn by_enum_ty<'a>(
-                    by: &String,
-                    enum_map: &'a KeyRangeMap<Type>,
-                    map: &Map<String, Value>,
-                    fields: &[Field],
-                    is_read: bool,
-                ) -> Result<&'a Type, evalexpr::EvalexprError> {
-                    let k = map
-                        .get(by)
-                        .ok_or(evalexpr::EvalexprError::CustomMessage(format!(
-                            "未找到引用键: {}",
-                            by
-                        )))?
-                        .clone();
-                    let k = fields
-                        .iter()
-                        .find(|Field { name, .. }| name == by)
-                        .ok_or(evalexpr::EvalexprError::CustomMessage(format!(
-                            "未找到引用键的类型定义: {}",
-                            by
-                        )))?
-                        .ty
-                        .convert(&k, is_read)
-                        .map_err(|e| evalexpr::EvalexprError::CustomMessage(format!("{}", e)))?
-                        .as_i64()
-                        .ok_or(evalexpr::EvalexprError::CustomMessage(format!(
-                            "引用键({})无法转化为整数",
-                            by
-                        )))?;
-                    enum_map
-                        .get(&k)
-                        .ok_or(evalexpr::EvalexprError::CustomMessage(format!(
-                            "未能找到引用键({})对应的类型({})",
-                            by, k
-                        )))
-                }
-
-                let mut rm = Map::new();
-                for Field { name, ty } in fields {
-                    if let Some((k, v)) = map.remove_entry(name) {
-                        let ty = match ty {
-                            Type::Enum { by, map, .. } => {
-                                by_enum_ty(by, map, &rm, fields, is_read)?
-                            }
-                            Type::Encrypt { inner_type, .. } => {
-                                if let Type::Enum { by, map, .. } = inner_type.as_ref() {
-                                    by_enum_ty(by, map, &rm, fields, is_read)?
-                                } else {
-                                    inner_type
-                                }
-                            }
-                            _ => ty,
-                        };
-                        rm.insert(k, ty.convert(&v, is_read)?);
-                    }
-                }
-                rm.append(&mut map);
-                Ok(Value::Object(rm))
+            (
+                Type::Struct {
+                    fields,
+                    definitions,
+                    ..
+                },
+                Value::Object(map),
+            ) => ctx.with_definitions(definitions, |ctx| convert_struct(fields, map, is_read, ctx)),
+            (Type::Ref { name }, value) => {
+                let (ty, ctx) = ctx.enter(name)?;
+                ty.convert_ctx(&value, is_read, &ctx)
             }
             (Type::Array { element_type, .. }, Value::Array(array)) => {
                 let a = array
                     .iter()
-                    .map(|v| element_type.convert(v, is_read))
+                    .map(|v| element_type.convert_ctx(v, is_read, ctx))
                     .collect::<Result<Vec<_>, _>>()?;
                 Ok(Value::Array(a))
             }
-            (Type::Encrypt { inner_type, .. }, value) => inner_type.convert(&value, is_read),
+            (Type::Encrypt { inner_type, .. } | Type::Compressed { inner_type, .. }, value) => {
+                inner_type.convert_ctx(&value, is_read, ctx)
+            }
+            (Type::Pointer { target, .. }, value) => target.convert_ctx(&value, is_read, ctx),
             (_, value) => Ok(value),
         }
     }
 }
+
+fn convert_struct(
+    fields: &[Field],
+    mut map: Map<String, Value>,
+    is_read: bool,
+    ctx: &Context,
+) -> Result<Value, evalexpr::EvalexprError> {
+    fn by_enum_ty<'a>(
+        by: &String,
+        enum_map: &'a KeyRangeMap<Type>,
+        map: &Map<String, Value>,
+        fields: &[Field],
+        is_read: bool,
+        ctx: &Context,
+    ) -> Result<&'a Type, evalexpr::EvalexprError> {
+        let k = map
+            .get(by)
+            .ok_or(evalexpr::EvalexprError::CustomMessage(format!(
+                "未找到引用键: {}",
+                by
+            )))?
+            .clone();
+        let k = fields
+            .iter()
+            .find(|Field { name, .. }| name == by)
+            .ok_or(evalexpr::EvalexprError::CustomMessage(format!(
+                "未找到引用键的类型定义: {}",
+                by
+            )))?
+            .ty
+            .convert_ctx(&k, is_read, ctx)
+            .map_err(|e| evalexpr::EvalexprError::CustomMessage(format!("{}", e)))?
+            .as_i64()
+            .ok_or(evalexpr::EvalexprError::CustomMessage(format!(
+                "引用键({})无法转化为整数",
+                by
+            )))?;
+        enum_map
+            .get(&k)
+            .ok_or(evalexpr::EvalexprError::CustomMessage(format!(
+                "未能找到引用键({})对应的类型({})",
+                by, k
+            )))
+    }
+
+    let mut rm = Map::new();
+    for Field { name, ty, .. } in fields {
+        if let Some((k, v)) = map.remove_entry(name) {
+            let (ty, ctx) = ctx.resolve(ty)?;
+            let ty = match ty {
+                Type::Enum { by, map, .. } => {
+                    by_enum_ty(by, map, &rm, fields, is_read, &ctx)?
+                }
+                Type::Encrypt { inner_type, .. } | Type::Compressed { inner_type, .. } => {
+                    if let Type::Enum { by, map, .. } = inner_type.as_ref() {
+                        by_enum_ty(by, map, &rm, fields, is_read, &ctx)?
+                    } else {
+                        inner_type
+                    }
+                }
+                _ => ty,
+            };
+            rm.insert(k, ty.convert_ctx(&v, is_read, &ctx)?);
+        }
+    }
+    rm.append(&mut map);
+    Ok(Value::Object(rm))
+}
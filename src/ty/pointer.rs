@@ -0,0 +1,50 @@
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+
+use crate::error::{ReadBinError, WriteBinError};
+use crate::ty::utils::as_u64;
+use crate::ty::Context;
+use crate::{Type, Value};
+
+/// 指针偏移量的起始位置
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub enum PointerBase {
+    /// 整个数据流的起始位置
+    #[default]
+    Buffer,
+    /// 所在结构体的起始位置。不在结构体中时与[`PointerBase::Buffer`]相同
+    Struct,
+}
+
+pub fn read_pointer<'a>(
+    data: &'a BitSlice<u8, Msb0>,
+    offset_type: &Type,
+    target: &Type,
+    base: PointerBase,
+    ctx: &Context,
+) -> Result<(Value, &'a BitSlice<u8, Msb0>), ReadBinError> {
+    let (offset, d) = offset_type.read_ctx(data, ctx)?;
+    let offset = as_u64(&offset)
+        .and_then(|o| usize::try_from(o).ok())
+        .and_then(|o| o.checked_mul(8))
+        .ok_or_else(|| ReadBinError::PointerError(offset.to_string()))?;
+
+    let start = ctx.pointer_start(base, data);
+    if start.len() < offset {
+        return Err(ReadBinError::Incomplete);
+    }
+    let (value, rest) = target.read_ctx(&start[offset..], ctx)?;
+    ctx.set_pointed_end(base, start.len() - rest.len());
+    Ok((value, d))
+}
+
+pub fn write_pointer(
+    value: &Value,
+    offset_type: &Type,
+    target: &Type,
+    base: PointerBase,
+    ctx: &Context,
+) -> Result<BitVec<u8, Msb0>, WriteBinError> {
+    let target = target.write_ctx(value, ctx)?;
+    let offset = ctx.add_pointer(base, target)?;
+    offset_type.write_ctx(&offset.into(), ctx)
+}
@@ -1,8 +1,8 @@
 use deku::bitvec::{BitSlice, Msb0};
 
 use crate::error::ReadBinError;
-use crate::ty::utils::get_data_by_size;
-use crate::ty::{BytesSize, Length};
+use crate::ty::utils::{eval_size, get_data_by_size};
+use crate::ty::{BytesSize, Context, Length};
 use crate::Type;
 use crate::Value;
 
@@ -11,6 +11,7 @@ pub fn read_array<'a>(
     length: &Option<Length>,
     size: &Option<BytesSize>,
     data: &'a BitSlice<u8, Msb0>,
+    ctx: &Context,
 ) -> Result<(Value, &'a BitSlice<u8, Msb0>), ReadBinError> {
     let src = data;
     let mut data = get_data_by_size(data, size, None)?;
@@ -19,11 +20,44 @@ pub fn read_array<'a>(
     let (mut ret, len) = match length {
         Some(Length::Fixed(size)) => (Vec::with_capacity(*size), *size),
         Some(Length::By(by)) => return Err(ReadBinError::ByKeyNotFound(by.clone())),
+        Some(Length::Expr { expr }) => {
+            let len = eval_size(expr, None)?;
+            if len == 0 {
+                return Ok((Value::Array(vec![]), src));
+            }
+            (Vec::with_capacity(len), len)
+        }
+        Some(Length::Until {
+            until,
+            include,
+            consume,
+        }) => {
+            let mut ret = vec![];
+            loop {
+                let (s, d) = ty.read_ctx(data, ctx)?;
+                if until.matches(&s)? {
+                    if *include {
+                        ret.push(s);
+                    }
+                    if *consume {
+                        data = d;
+                    }
+                    break;
+                }
+                // 成员不占用数据时，无法继续读取到终止成员
+                if d.len() == data.len() {
+                    return Err(ReadBinError::TerminatorError("成员的大小为0，无法找到终止成员"));
+                }
+                data = d;
+                ret.push(s);
+            }
+            return Ok((Value::Array(ret), &src[data_len - data.len()..]));
+        }
         None => (vec![], 0),
     };
 
     loop {
-        match ty.read(data) {
+        match ty.read_ctx(data, ctx) {
             Ok((s, d)) => {
                 data = d;
                 ret.push(s);
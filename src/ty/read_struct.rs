@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::collections::HashMap;
 
 use deku::bitvec::{BitSlice, Msb0};
@@ -5,23 +6,44 @@ use deku::ctx::{BitSize, Limit};
 use deku::DekuRead;
 use serde_json::Map;
 
-use crate::ty::utils::get_data_by_size;
-use crate::ty::{BytesSize, Field, Length};
+use crate::ty::bit_order::{packed_type, read_lsb0};
+use crate::ty::utils::{align_padding, eval_size, get_data_by_size, siblings_ctx, to_json_value};
+use crate::ty::{BitOrder, BytesSize, Context, Field, Length};
 use crate::{ReadBinError, Type, Value};
 
 pub fn read_struct<'a>(
     fields: &[Field],
     size: &Option<BytesSize>,
     data: &'a BitSlice<u8, Msb0>,
+    ctx: &Context,
 ) -> Result<(Value, &'a BitSlice<u8, Msb0>), ReadBinError> {
     let src = data;
+    let bit_order = ctx.bit_order();
+    let pointed_end = Cell::new(0);
+    let ctx = &ctx.enter_struct(src, &pointed_end);
     let mut data = get_data_by_size(&data, size, None)?;
+    let start = data;
     let data_len = data.len();
     let mut ret: Map<String, Value> = Map::with_capacity(fields.len());
     let mut key_pos: HashMap<&String, usize> = HashMap::with_capacity(fields.len());
 
-    for Field { name, ty } in fields {
+    for field in fields {
+        let Field { name, ty, .. } = field;
         key_pos.insert(name, src.len() - data.len());
+        if !field.exists(&ret)? {
+            continue;
+        }
+        let (ty, ctx) = ctx.resolve(ty)?;
+
+        if let (BitOrder::Lsb0, Some((bits, packed))) = (bit_order, packed_type(ty, &ctx)) {
+            let value = read_lsb0(start, data_len - data.len(), bits)
+                .filter(|_| data.len() >= bits)
+                .ok_or(ReadBinError::Incomplete)?;
+            ret.insert(name.clone(), packed.read_ctx(&value, &ctx)?.0);
+            data = &data[bits..];
+            check_assert(field, &ret)?;
+            continue;
+        }
 
         data = match ty {
             Type::Checksum {
@@ -86,14 +108,56 @@ pub fn read_struct<'a>(
             } => {
                 let en_data = get_data_by_size(data, size, Some(&ret))?;
                 let de_data = on_read.decrypt(en_data)?;
-                read_normal_field(name, inner_type, &de_data, &mut ret)?;
+                read_normal_field(name, inner_type, &de_data, &mut ret, &ctx)?;
                 &data[en_data.len()..]
             }
-            _ => read_normal_field(name, ty, data, &mut ret)?,
+            Type::Compressed {
+                algorithm,
+                inner_type,
+                size,
+            } => {
+                let compressed = get_data_by_size(data, size, Some(&ret))?;
+                let de_data = algorithm.decompress(compressed, ctx.max_decompressed_size())?;
+                read_normal_field(name, inner_type, &de_data, &mut ret, &ctx)?;
+                &data[compressed.len()..]
+            }
+            Type::Padding { .. } => ty.read_ctx(data, &ctx)?.1,
+            Type::Computed { expr } => {
+                let value = evalexpr::eval_with_context(expr, &siblings_ctx(&ret)?)?;
+                ret.insert(name.clone(), to_json_value(value));
+                data
+            }
+            Type::Align { align, .. } => {
+                let pad = align_padding(data_len - data.len(), align.0);
+                if data.len() < pad {
+                    return Err(ReadBinError::Incomplete);
+                }
+                &data[pad..]
+            }
+            _ => read_normal_field(name, ty, data, &mut ret, &ctx)?,
         };
+        check_assert(field, &ret)?;
     }
 
-    Ok((Value::Object(ret), &src[data_len - data.len()..]))
+    let mut end = data_len - data.len();
+    if bit_order == BitOrder::Lsb0 {
+        end = end.next_multiple_of(8);
+    }
+    // 跳过结构体之后被指向的数据
+    let end = end.max(pointed_end.get()).min(src.len());
+    Ok((Value::Object(ret), &src[end..]))
+}
+
+/// 读取字段之后检查断言
+fn check_assert(field: &Field, siblings: &Map<String, Value>) -> Result<(), ReadBinError> {
+    if field.holds(siblings)? {
+        Ok(())
+    } else {
+        Err(ReadBinError::AssertionFailed {
+            field: field.name.clone(),
+            expr: field.assert.clone().unwrap_or_default(),
+        })
+    }
 }
 
 fn read_normal_field<'a>(
@@ -101,30 +165,43 @@ fn read_normal_field<'a>(
     ty: &Type,
     data: &'a BitSlice<u8, Msb0>,
     result: &mut Map<String, Value>,
+    ctx: &Context,
 ) -> Result<&'a BitSlice<u8, Msb0>, ReadBinError> {
+    let (ty, ctx) = ctx.resolve(ty)?;
     let mut ty = ty.clone();
+    // 长度为0时不读取任何成员。`Length::Fixed(0)`表示读取到数据结束，不能用于表示空数组
+    let mut empty = false;
     if let Type::Array {
         length: Some(length),
         ..
     } = &mut ty
     {
-        if let Length::By(by) = length {
-            let len = result
+        let len = if let Length::By(by) = length {
+            Some(result
                 .get(by)
                 .ok_or(ReadBinError::ByKeyNotFound(by.clone()))?
                 .as_u64()
                 .ok_or(ReadBinError::LengthTargetIsInvalid(by.clone()))?
-                as usize;
-            *length = Length::Fixed(len)
+                as usize)
+        } else if let Length::Expr { expr } = length {
+            Some(eval_size(expr, Some(result))?)
+        } else {
+            None
+        };
+        if let Some(len) = len {
+            empty = len == 0;
+            *length = Length::Fixed(len);
         }
     }
 
-    let (d, fixed_size) = if let Type::Bin { size }
-    | Type::String { size }
+    let (d, fixed_size) = if let Type::Bin { size, .. }
+    | Type::String { size, .. }
     | Type::Array { size, .. }
     | Type::Struct { size, .. }
+    | Type::Tlv { size, .. }
     | Type::Enum { size, .. }
-    | Type::Encrypt { size, .. } = &mut ty
+    | Type::Encrypt { size, .. }
+    | Type::Compressed { size, .. } = &mut ty
     {
         let fs = size.is_some();
         let d = get_data_by_size(data, size, Some(&result))?;
@@ -147,7 +224,11 @@ fn read_normal_field<'a>(
             .ok_or(ReadBinError::EnumKeyNotFound(key))?;
     }
 
-    let (v, d2) = ty.read(d)?;
+    let (v, d2) = if empty {
+        (Value::Array(vec![]), d)
+    } else {
+        ty.read_ctx(d, &ctx)?
+    };
     result.insert(name.clone(), v);
     if fixed_size {
         Ok(&data[d.len()..])
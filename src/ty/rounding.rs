@@ -0,0 +1,37 @@
+/// 取整方式
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub enum Rounding {
+    /// 四舍五入（中间值远离零）
+    #[default]
+    Nearest,
+    /// 四舍六入五成双（中间值取偶数）
+    NearestEven,
+    /// 向下取整
+    Floor,
+    /// 向上取整
+    Ceil,
+    /// 向零取整
+    Truncate,
+}
+
+impl Rounding {
+    pub fn round(&self, value: f64) -> f64 {
+        match self {
+            Rounding::Nearest => value.round(),
+            Rounding::NearestEven => value.round_ties_even(),
+            Rounding::Floor => value.floor(),
+            Rounding::Ceil => value.ceil(),
+            Rounding::Truncate => value.trunc(),
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Rounding::Nearest => "四舍五入",
+            Rounding::NearestEven => "四舍六入五成双",
+            Rounding::Floor => "向下取整",
+            Rounding::Ceil => "向上取整",
+            Rounding::Truncate => "向零取整",
+        }
+    }
+}
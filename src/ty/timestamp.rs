@@ -0,0 +1,170 @@
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{ReadBinError, WriteBinError};
+use crate::ty::utils::as_i64;
+use crate::Value;
+
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+
+/// 时间戳的起始时间
+///
+/// **示例：**
+/// ```rust
+/// use bin2json::ty::Epoch;
+///
+/// assert_eq!(Epoch::Gps, serde_json::from_str(r#""Gps""#)?);
+/// let epoch: Epoch = serde_json::from_str(r#"{ "Custom": "2000-01-01T00:00:00Z" }"#)?;
+/// assert_eq!(946684800, epoch.unix_seconds());
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub enum Epoch {
+    /// 1970-01-01T00:00:00Z
+    #[default]
+    Unix,
+    /// 1980-01-06T00:00:00Z，不计算闰秒
+    Gps,
+    /// 1900-01-01T00:00:00Z
+    Ntp,
+    /// 1601-01-01T00:00:00Z，即Windows`FILETIME`的起始时间。`FILETIME`的精度为[`Resolution::HundredNanoseconds`]
+    Windows,
+    /// 自定义的起始时间
+    Custom(DateTime<Utc>),
+}
+
+impl Epoch {
+    /// 起始时间对应的Unix时间戳，单位为秒
+    pub fn unix_seconds(&self) -> i64 {
+        match self {
+            Epoch::Unix => 0,
+            Epoch::Gps => 315_964_800,
+            Epoch::Ntp => -2_208_988_800,
+            Epoch::Windows => -11_644_473_600,
+            Epoch::Custom(dt) => dt.timestamp(),
+        }
+    }
+
+    fn unix_nanos(&self) -> i128 {
+        let nanos = if let Epoch::Custom(dt) = self {
+            dt.timestamp_subsec_nanos()
+        } else {
+            0
+        };
+        self.unix_seconds() as i128 * NANOS_PER_SEC + nanos as i128
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Epoch::Unix => "Unix",
+            Epoch::Gps => "GPS",
+            Epoch::Ntp => "NTP",
+            Epoch::Windows => "Windows",
+            Epoch::Custom(_) => "自定义",
+        }
+    }
+}
+
+/// 时间戳的精度
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub enum Resolution {
+    #[default]
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    /// 100纳秒
+    HundredNanoseconds,
+    Nanoseconds,
+}
+
+impl Resolution {
+    /// 每个单位对应的纳秒数
+    pub const fn nanos(&self) -> i64 {
+        match self {
+            Resolution::Seconds => 1_000_000_000,
+            Resolution::Milliseconds => 1_000_000,
+            Resolution::Microseconds => 1_000,
+            Resolution::HundredNanoseconds => 100,
+            Resolution::Nanoseconds => 1,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Resolution::Seconds => "秒",
+            Resolution::Milliseconds => "毫秒",
+            Resolution::Microseconds => "微秒",
+            Resolution::HundredNanoseconds => "100纳秒",
+            Resolution::Nanoseconds => "纳秒",
+        }
+    }
+}
+
+/// 将原始数值转换为RFC 3339格式的时间
+pub fn to_rfc3339(
+    raw: &Value,
+    epoch: &Epoch,
+    resolution: Resolution,
+    timezone: Option<FixedOffset>,
+) -> Result<Value, ReadBinError> {
+    let error = || ReadBinError::TimestampError(raw.to_string());
+
+    let ts = as_i64(raw)
+        .or_else(|| raw.as_str().and_then(|s| s.parse().ok()))
+        .ok_or_else(error)?;
+    let nanos = epoch.unix_nanos() + ts as i128 * resolution.nanos() as i128;
+    let secs = i64::try_from(nanos.div_euclid(NANOS_PER_SEC)).map_err(|_| error())?;
+    let dt = DateTime::from_timestamp(secs, nanos.rem_euclid(NANOS_PER_SEC) as u32)
+        .ok_or_else(error)?;
+
+    let s = match timezone {
+        Some(tz) => dt.with_timezone(&tz).to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        None => dt.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+    };
+    Ok(s.into())
+}
+
+/// 将RFC 3339格式的时间转换为原始数值。输入为整数时直接返回该值
+///
+/// 精度不足的部分向下取整
+pub fn from_rfc3339(
+    value: &Value,
+    epoch: &Epoch,
+    resolution: Resolution,
+) -> Result<Value, WriteBinError> {
+    let s = match value {
+        Value::String(s) => s,
+        Value::Number(n) if n.is_i64() || n.is_u64() => return Ok(value.clone()),
+        _ => return Err(WriteBinError::TypeError("Timestamp")),
+    };
+    let error = || WriteBinError::TimestampError(s.clone());
+
+    let dt = DateTime::parse_from_rfc3339(s).map_err(|_| error())?;
+    let nanos = dt.timestamp() as i128 * NANOS_PER_SEC + dt.timestamp_subsec_nanos() as i128
+        - epoch.unix_nanos();
+    let ts = i64::try_from(nanos.div_euclid(resolution.nanos() as i128))
+        .map_err(|_| WriteBinError::ValueOverflowOf("Timestamp"))?;
+    Ok(ts.into())
+}
+
+pub fn se_timezone<S>(tz: &Option<FixedOffset>, ser: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    tz.map(|tz| tz.to_string()).serialize(ser)
+}
+
+pub fn de_timezone<'de, D>(de: D) -> Result<Option<FixedOffset>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(de)?
+        .map(|s| match s.trim() {
+            "Z" | "z" | "UTC" => Ok(FixedOffset::east_opt(0).unwrap()),
+            tz => tz
+                .parse()
+                .map_err(|_| D::Error::custom(format!("无效的时区: {}", s))),
+        })
+        .transpose()
+}
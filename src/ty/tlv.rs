@@ -0,0 +1,136 @@
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+use deku::ctx::Limit;
+use deku::DekuRead;
+use serde_json::Map;
+
+use crate::error::{ReadBinError, WriteBinError};
+use crate::range::{KeyRange, KeyRangeMap};
+use crate::ty::utils::as_i64;
+use crate::ty::Context;
+use crate::{Type, Value};
+
+const TYPE_NAME: &str = "Tlv";
+
+/// TLV序列的输出格式
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub enum TlvFormat {
+    /// 由标签与值组成的对象数组，如：`[{ "tag": "name", "value": "abc" }, { "tag": 9, "value": [1, 2] }]`
+    #[default]
+    List,
+    /// 以标签名称为键的对象，如：`{ "name": "abc", "9": [1, 2] }`。标签重复时只保留最后一个值
+    Object,
+}
+
+/// 未定义值类型的标签按原始字节读写
+const RAW_TYPE: Type = Type::Bin {
+    size: None,
+    padding: None,
+};
+
+/// 读取整个`data`中的TLV序列
+pub fn read_tlv<'a>(
+    data: &'a BitSlice<u8, Msb0>,
+    tag: &Type,
+    length: &Type,
+    values: &KeyRangeMap<Type>,
+    names: &KeyRangeMap<String>,
+    format: TlvFormat,
+    ctx: &Context,
+) -> Result<(Value, &'a BitSlice<u8, Msb0>), ReadBinError> {
+    let mut data = data;
+    let mut list = vec![];
+    let mut object = Map::new();
+
+    while !data.is_empty() {
+        let (t, d) = tag.read_ctx(data, ctx)?;
+        let key = as_i64(&t).ok_or_else(|| ReadBinError::TlvError(format!("标签({})不是整数", t)))?;
+        let (l, d) = length.read_ctx(d, ctx)?;
+        let (len, bits) = as_i64(&l)
+            .and_then(|l| usize::try_from(l).ok())
+            .and_then(|l| Some((l, l.checked_mul(8)?)))
+            .ok_or_else(|| ReadBinError::TlvError(format!("长度({})无效", l)))?;
+        if d.len() < bits {
+            return Err(ReadBinError::Incomplete);
+        }
+
+        let value_data = &d[..bits];
+        let value = match values.get(&key) {
+            Some(value_type) => value_type.read_ctx(value_data, ctx)?.0,
+            None => Vec::<u8>::read(value_data, Limit::new_count(len))?.1.into(),
+        };
+        data = &d[bits..];
+
+        let name = names.get(&key).cloned();
+        match format {
+            TlvFormat::List => {
+                let mut item = Map::with_capacity(2);
+                item.insert("tag".to_string(), name.map_or(t, Value::String));
+                item.insert("value".to_string(), value);
+                list.push(Value::Object(item));
+            }
+            TlvFormat::Object => {
+                object.insert(name.unwrap_or_else(|| key.to_string()), value);
+            }
+        }
+    }
+
+    let value = match format {
+        TlvFormat::List => Value::Array(list),
+        TlvFormat::Object => Value::Object(object),
+    };
+    Ok((value, data))
+}
+
+pub fn write_tlv(
+    value: &Value,
+    tag: &Type,
+    length: &Type,
+    values: &KeyRangeMap<Type>,
+    names: &KeyRangeMap<String>,
+    ctx: &Context,
+) -> Result<BitVec<u8, Msb0>, WriteBinError> {
+    let key_of = |name: &str| -> Result<i64, WriteBinError> {
+        match names.find_key(&name.to_string()) {
+            Some(KeyRange::Value(key)) => Ok(key),
+            _ => name
+                .parse()
+                .map_err(|_| WriteBinError::MappingError(name.to_string())),
+        }
+    };
+
+    let items = match value {
+        Value::Array(list) => list
+            .iter()
+            .map(|item| {
+                let tag = item.get("tag").ok_or(WriteBinError::TypeError(TYPE_NAME))?;
+                let key = match tag {
+                    Value::String(name) => key_of(name)?,
+                    tag => as_i64(tag).ok_or(WriteBinError::TypeError(TYPE_NAME))?,
+                };
+                let value = item.get("value").ok_or(WriteBinError::TypeError(TYPE_NAME))?;
+                Ok((key, value))
+            })
+            .collect::<Result<Vec<_>, WriteBinError>>()?,
+        Value::Object(map) => map
+            .iter()
+            .map(|(name, value)| Ok((key_of(name)?, value)))
+            .collect::<Result<Vec<_>, WriteBinError>>()?,
+        _ => return Err(WriteBinError::TypeError(TYPE_NAME)),
+    };
+
+    let mut output = BitVec::new();
+    for (key, value) in items {
+        let mut data = values.get(&key).unwrap_or(&RAW_TYPE).write_ctx(value, ctx)?;
+        if data.len() % 8 != 0 {
+            return Err(WriteBinError::BytesSizeError);
+        }
+        output.append(&mut tag.write_ctx(&key.into(), ctx)?);
+        output.append(&mut length.write_ctx(&(data.len() / 8).into(), ctx)?);
+        output.append(&mut data);
+    }
+    Ok(output)
+}
+
+pub fn default_names() -> KeyRangeMap<String> {
+    KeyRangeMap::new()
+}
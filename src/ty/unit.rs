@@ -4,7 +4,8 @@ use super::{BitSize, Endian};
 
 /// 类型的大小与字节顺序
 ///
-/// 默认`endian`为[`Endian::Big`]，`size`为[`None`]
+/// 默认`endian`与`size`均为[`None`]。未指定`endian`时使用所在结构体的默认字节顺序
+/// （见[`Type::Struct`](crate::Type::Struct)的`default_endian`），均未指定时为[`Endian::Big`]
 /// **示例：**
 /// ```rust
 /// use bin2json::ty::{Endian, BitSize, Unit};
@@ -14,7 +15,7 @@ use super::{BitSize, Endian};
 ///     "endian": "Little"
 /// }
 /// "#)?;
-/// assert_eq!( Unit { endian: Endian::Little, size: None }, unit);
+/// assert_eq!( Unit { endian: Some(Endian::Little), size: None }, unit);
 ///
 /// let unit: Unit = serde_json::from_str(r#"
 /// {
@@ -22,7 +23,7 @@ use super::{BitSize, Endian};
 ///     "size": { "type": "Bits", "value": 100 }
 /// }
 /// "#)?;
-/// assert_eq!(Unit { endian: Endian::Little, size: Some(BitSize(100)) }, unit);
+/// assert_eq!(Unit { endian: Some(Endian::Little), size: Some(BitSize(100)) }, unit);
 ///
 /// let unit: Unit = serde_json::from_str(r#"
 /// {
@@ -30,13 +31,21 @@ use super::{BitSize, Endian};
 ///     "size": { "type": "Bytes", "value": 200 }
 /// }
 /// "#)?;
-/// assert_eq!(unit, Unit {endian: Endian::Big, size: Some(BitSize(200 * 8))});
+/// assert_eq!(unit, Unit {endian: Some(Endian::Big), size: Some(BitSize(200 * 8))});
+///
+/// let unit: Unit = serde_json::from_str(r#"
+/// {
+///     "size": { "type": "Bits", "value": 4 }
+/// }
+/// "#)?;
+/// assert_eq!(unit, Unit {endian: None, size: Some(BitSize(4))});
 /// # Ok::<_, serde_json::Error>(())
 /// ```
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
 pub struct Unit {
     /// 字节顺序
-    pub endian: Endian,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endian: Option<Endian>,
     /// 实际要读取的大小
     #[serde(default)]
     #[serde(serialize_with = "se_op_size")]
@@ -47,30 +56,21 @@ pub struct Unit {
 impl Unit {
     pub fn new(endian: Endian, size: BitSize) -> Self {
         Self {
-            endian,
+            endian: Some(endian),
             size: Some(size),
         }
     }
 
     pub const fn big_endian() -> Self {
         Self {
-            endian: Endian::Big,
+            endian: Some(Endian::Big),
             size: None,
         }
     }
 
     pub const fn little_endian() -> Self {
         Self {
-            endian: Endian::Little,
-            size: None,
-        }
-    }
-}
-
-impl Default for Unit {
-    fn default() -> Self {
-        Self {
-            endian: Endian::Big,
+            endian: Some(Endian::Little),
             size: None,
         }
     }
@@ -92,6 +92,23 @@ enum SizeDef {
     Bytes(usize),
 }
 
+pub(crate) fn se_size<S>(s: &BitSize, ser: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    SizeDef::Bits(s.0).serialize(ser)
+}
+
+pub(crate) fn de_size<'de, D>(de: D) -> Result<BitSize, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    SizeDef::deserialize(de).map(|s| match s {
+        SizeDef::Bits(size) => BitSize(size),
+        SizeDef::Bytes(size) => BitSize(size * 8),
+    })
+}
+
 fn se_op_size<S>(s: &Option<BitSize>, ser: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -38,6 +38,27 @@ pub fn check_size(size: &Option<BytesSize>, out: &BitVec<u8, Msb0>) -> Result<()
     Ok(())
 }
 
+/// 使用`fill`循环填充指定比特数的数据，`fill`为空时填充0
+pub fn fill_bits(fill: &[u8], bits: usize) -> BitVec<u8, Msb0> {
+    let fill = if fill.is_empty() { &[0u8][..] } else { fill };
+    let bytes = fill
+        .iter()
+        .copied()
+        .cycle()
+        .take(bits.div_ceil(8))
+        .collect::<Vec<_>>();
+    bytes.view_bits::<Msb0>()[..bits].to_bitvec()
+}
+
+/// 从`offset`对齐到`align`的整数倍需要填充的比特数
+pub fn align_padding(offset: usize, align: usize) -> usize {
+    if align == 0 {
+        0
+    } else {
+        (align - offset % align) % align
+    }
+}
+
 pub fn to_json_value(value: evalexpr::Value) -> serde_json::Value {
     match value {
         evalexpr::Value::String(s) => s.into(),
@@ -55,6 +76,24 @@ pub fn set_ctx(
     value: &serde_json::Value,
     prefix: Option<String>,
     ctx: &mut evalexpr::HashMapContext,
+) -> evalexpr::EvalexprResult<()> {
+    set_ctx_with(value, prefix, ctx, false)
+}
+
+/// 与[`set_ctx`]相同，但整数设置为整型变量，使`self.type == 0`等比较可以成立
+pub fn set_int_ctx(
+    value: &serde_json::Value,
+    prefix: Option<String>,
+    ctx: &mut evalexpr::HashMapContext,
+) -> evalexpr::EvalexprResult<()> {
+    set_ctx_with(value, prefix, ctx, true)
+}
+
+fn set_ctx_with(
+    value: &serde_json::Value,
+    prefix: Option<String>,
+    ctx: &mut evalexpr::HashMapContext,
+    keep_int: bool,
 ) -> evalexpr::EvalexprResult<()> {
     let ident = prefix.unwrap_or_else(|| "self".to_string());
 
@@ -62,7 +101,10 @@ pub fn set_ctx(
         serde_json::Value::Null => ctx.set_value(ident, evalexpr::Value::Empty)?,
         serde_json::Value::Bool(b) => ctx.set_value(ident, evalexpr::Value::Boolean(*b))?,
         serde_json::Value::Number(n) => {
-            let v = evalexpr::Value::Float(n.as_f64().unwrap());
+            let v = match n.as_i64() {
+                Some(i) if keep_int => evalexpr::Value::Int(i),
+                _ => evalexpr::Value::Float(n.as_f64().unwrap()),
+            };
             ctx.set_value(ident, v)?;
         }
         serde_json::Value::String(s) => ctx.set_value(ident, evalexpr::Value::String(s.clone()))?,
@@ -73,19 +115,28 @@ pub fn set_ctx(
             )?;
             for (idx, v) in a.iter().enumerate() {
                 let ident = format!("{}[{}]", &ident, idx);
-                set_ctx(v, Some(ident), ctx)?
+                set_ctx_with(v, Some(ident), ctx, keep_int)?
             }
         }
         serde_json::Value::Object(m) => {
             for (k, v) in m {
                 let ident = format!("{}.{}", ident, k);
-                set_ctx(v, Some(ident), ctx)?;
+                set_ctx_with(v, Some(ident), ctx, keep_int)?;
             }
         }
     };
     Ok(())
 }
 
+/// 使用同级字段的值创建表达式的上下文，变量名为`self.field_name`
+pub fn siblings_ctx(siblings: &Map<String, Value>) -> evalexpr::EvalexprResult<evalexpr::HashMapContext> {
+    let mut ctx = evalexpr::HashMapContext::new();
+    for (k, v) in siblings {
+        set_int_ctx(v, Some(format!("self.{}", k)), &mut ctx)?;
+    }
+    Ok(ctx)
+}
+
 pub fn get_data_by_size<'a>(
     data: &'a BitSlice<u8, Msb0>,
     size: &Option<BytesSize>,
@@ -112,6 +163,7 @@ pub fn get_data_by_size<'a>(
             }
             v.len()
         }
+        Some(BytesSize::Expr { expr }) => eval_size(expr, by_map)?,
         Some(BytesSize::By(ref by) | BytesSize::Enum { ref by, .. }) => {
             if let Some(map) = by_map {
                 let by_value = map.get(by).ok_or(ReadBinError::ByKeyNotFound(by.clone()))?;
@@ -136,6 +188,18 @@ pub fn get_data_by_size<'a>(
     }
 }
 
+/// 使用同级字段的值计算大小或长度表达式
+pub fn eval_size(expr: &str, siblings: Option<&Map<String, Value>>) -> Result<usize, ReadBinError> {
+    let ctx = match siblings {
+        Some(siblings) => siblings_ctx(siblings)?,
+        None => evalexpr::HashMapContext::new(),
+    };
+    let v = to_json_value(evalexpr::eval_with_context(expr, &ctx)?);
+    as_u64(&v)
+        .map(|v| v as usize)
+        .ok_or_else(|| ReadBinError::LengthTargetIsInvalid(expr.to_string()))
+}
+
 pub fn as_i64(num: &Value) -> Option<i64> {
     num.as_i64().or_else(|| {
         if let Some(f) = num.as_f64() {
@@ -0,0 +1,101 @@
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+use deku::{DekuRead, DekuWrite};
+
+use crate::error::{ReadBinError, WriteBinError};
+use crate::ty::utils::{as_i64, as_u64};
+use crate::Value;
+
+/// 变长整数最多占用的字节数
+const MAX_BYTES: usize = 10;
+
+pub fn read_var_int(
+    data: &BitSlice<u8, Msb0>,
+    signed: bool,
+    zigzag: bool,
+) -> Result<(Value, &BitSlice<u8, Msb0>), ReadBinError> {
+    let mut data = data;
+    let mut raw = 0u64;
+    let mut shift = 0;
+    let mut last = 0u8;
+
+    for idx in 0..MAX_BYTES {
+        let (d, b) = u8::read(data, ())?;
+        data = d;
+        last = b;
+
+        // 第10个字节只能存放最高的1位，有符号数的其余位为该位的符号扩展，只能为0x00或0x7f
+        let valid = if signed && !zigzag {
+            matches!(b & 0x7f, 0x00 | 0x7f)
+        } else {
+            b & 0x7e == 0
+        };
+        if idx == MAX_BYTES - 1 && !valid {
+            return Err(ReadBinError::VarIntOverflow);
+        }
+        raw |= ((b & 0x7f) as u64) << shift;
+        shift += 7;
+
+        if b & 0x80 == 0 {
+            break;
+        } else if idx == MAX_BYTES - 1 {
+            return Err(ReadBinError::VarIntOverflow);
+        }
+    }
+
+    let value = if !signed {
+        raw.into()
+    } else if zigzag {
+        ((raw >> 1) as i64 ^ -((raw & 1) as i64)).into()
+    } else {
+        let mut v = raw as i64;
+        if shift < 64 && last & 0x40 != 0 {
+            v |= -1i64 << shift;
+        }
+        v.into()
+    };
+
+    Ok((value, data))
+}
+
+pub fn write_var_int(
+    value: &Value,
+    signed: bool,
+    zigzag: bool,
+    output: &mut BitVec<u8, Msb0>,
+) -> Result<(), WriteBinError> {
+    const TYPE_NAME: &str = "VarInt";
+
+    let bytes = if !signed {
+        let mut v = as_u64(value).ok_or(WriteBinError::TypeError(TYPE_NAME))?;
+        let mut bytes = Vec::with_capacity(MAX_BYTES);
+        loop {
+            let b = (v & 0x7f) as u8;
+            v >>= 7;
+            if v == 0 {
+                bytes.push(b);
+                break;
+            }
+            bytes.push(b | 0x80);
+        }
+        bytes
+    } else if zigzag {
+        let v = as_i64(value).ok_or(WriteBinError::TypeError(TYPE_NAME))?;
+        return write_var_int(&(((v << 1) ^ (v >> 63)) as u64).into(), false, false, output);
+    } else {
+        let mut v = as_i64(value).ok_or(WriteBinError::TypeError(TYPE_NAME))?;
+        let mut bytes = Vec::with_capacity(MAX_BYTES);
+        loop {
+            let b = (v & 0x7f) as u8;
+            v >>= 7;
+            if (v == 0 && b & 0x40 == 0) || (v == -1 && b & 0x40 != 0) {
+                bytes.push(b);
+                break;
+            }
+            bytes.push(b | 0x80);
+        }
+        bytes
+    };
+
+    bytes.write(output, ())?;
+    Ok(())
+}
@@ -1,31 +1,66 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use deku::bitvec::{BitVec, Msb0};
+use evalexpr::ContextWithMutableVariables;
 use serde_json::{Map, Value};
 
 use crate::error::WriteBinError;
 use crate::range::KeyRange;
-use crate::ty::{BytesSize, Field, Length};
-use crate::ty::utils::check_size;
+use crate::ty::bit_order::{packed_type, to_lsb0};
+use crate::ty::{BitOrder, BytesSize, Context, Field, Length};
+use crate::ty::utils::{align_padding, as_u64, check_size, fill_bits, siblings_ctx, to_json_value};
 use crate::Type;
 
+/// 字段名称与字段类型、写入结果的映射
+type FieldBits<'a> = HashMap<&'a String, (&'a Type, Option<BitVec<u8, Msb0>>)>;
+
 pub fn write_struct(
     fields: &[Field],
     object: &Map<String, Value>,
+    ctx: &Context,
 ) -> Result<BitVec<u8, Msb0>, WriteBinError> {
+    let bit_order = ctx.bit_order();
     let mut result = fields.iter()
-        .map(|Field { name, ty }| (name, (ty, None)))
-        .collect::<HashMap<_, (&Type, Option<BitVec<u8, Msb0>>)>>();
+        .map(|Field { name, ty, .. }| (name, (ty, None)))
+        .collect::<FieldBits>();
+    // 因条件不成立而跳过的字段
+    let mut skipped = HashSet::new();
 
     // 重复原因：应对引用字段与Checksum或Sign嵌套
-    for _ in 0..2 {
+    for pass in 0..2 {
         let mut key_idx = HashMap::with_capacity(fields.len());
-        for Field { name, ty } in fields {
+        for field in fields {
+            let Field { name, ty, .. } = field;
             key_idx.insert(name, key_idx.len());
 
             if result[name].1.is_some() {
                 continue;
             }
+            let exists = if field.condition.is_some() {
+                let siblings = preceding_values(&fields[..key_idx[name]], object, &result, &skipped, ctx);
+                match field.exists(&siblings) {
+                    Ok(exists) => exists,
+                    // 引用的字段可能在之后的字段写入时才自动计算
+                    Err(evalexpr::EvalexprError::VariableIdentifierNotFound(_)) if pass == 0 => continue,
+                    Err(e) => return Err(e.into()),
+                }
+            } else {
+                true
+            };
+            let (ty, ctx) = ctx.resolve(ty)?;
+
+            if field.condition.is_some() {
+                let need_value = !matches!(
+                    ty,
+                    Type::Magic { .. } | Type::Padding { .. } | Type::Align { .. }
+                    | Type::Checksum { .. } | Type::Sign { .. } | Type::Computed { .. }
+                );
+                if (need_value && !object.contains_key(name)) || !exists {
+                    result.entry(name).or_insert_with(|| (ty, None)).1 = Some(BitVec::new());
+                    skipped.insert(name);
+                    continue;
+                }
+            }
 
             let bits = match (ty, object.get(name)) {
                 (
@@ -69,21 +104,35 @@ pub fn write_struct(
                             let bits = on_write.sign(&bits)?;
                             check_size(size, &bits)?;
                             if let Some(BytesSize::By(by) | BytesSize::Enum { by, .. }) = size {
-                                set_by_value(&mut result, ty, &bits, by)?;
+                                set_by_value(&mut result, ty, &bits, by, &ctx)?;
+                            } else if let Some(BytesSize::Expr { expr }) = size {
+                                set_by_expr(&mut result, expr, bytes_len(&bits)?, object, &ctx)?;
                             }
                             Some(bits)
                         }
                         _ => None
                     }
                 }
+                (Type::Align { align, fill }, _) => {
+                    let offset = fields[..key_idx[name]].iter()
+                        .map(|Field { name, .. }| result[name].1.as_ref().map(|v| v.len()))
+                        .sum::<Option<usize>>();
+                    if let Some(offset) = offset {
+                        Some(fill_bits(fill, align_padding(offset, align.0)))
+                    } else {
+                        continue;
+                    }
+                }
                 (Type::Encrypt { inner_type, on_write, size, .. }, value) => {
-                    let v = write_normal_field(inner_type, value, object, &mut result)?;
+                    let v = write_normal_field(inner_type, value, object, &mut result, &ctx)?;
                     if let Some(data) = v {
                         let data = on_write.encrypt(data)?;
                         check_size(size, &data)?;
 
                         if let Some(BytesSize::By(by) | BytesSize::Enum { by, .. }) = size {
-                            set_by_value(&mut result, ty, &data, by)?;
+                            set_by_value(&mut result, ty, &data, by, &ctx)?;
+                        } else if let Some(BytesSize::Expr { expr }) = size {
+                            set_by_expr(&mut result, expr, bytes_len(&data)?, object, &ctx)?;
                         }
 
                         Some(data)
@@ -91,43 +140,128 @@ pub fn write_struct(
                         None
                     }
                 }
-                (_, value) => {
-                    write_normal_field(ty, value, object, &mut result)?
+                (Type::Compressed { algorithm, inner_type, size }, value) => {
+                    let v = write_normal_field(inner_type, value, object, &mut result, &ctx)?;
+                    if let Some(data) = v {
+                        let data = algorithm.compress(data)?;
+                        check_size(size, &data)?;
+
+                        if let Some(BytesSize::By(by) | BytesSize::Enum { by, .. }) = size {
+                            set_by_value(&mut result, ty, &data, by, &ctx)?;
+                        } else if let Some(BytesSize::Expr { expr }) = size {
+                            set_by_expr(&mut result, expr, bytes_len(&data)?, object, &ctx)?;
+                        }
+
+                        Some(data)
+                    } else {
+                        None
+                    }
                 }
+                (_, value) => match (bit_order, packed_type(ty, &ctx)) {
+                    (BitOrder::Lsb0, Some((_, packed))) => {
+                        // 按字节内从低到高的顺序排列，合并所有字段后再转换
+                        write_normal_field(&packed, value, object, &mut result, &ctx)?
+                            .map(|mut bits| {
+                                bits.reverse();
+                                bits
+                            })
+                    }
+                    _ => write_normal_field(ty, value, object, &mut result, &ctx)?,
+                },
             };
             result.entry(name)
-                .or_insert_with(|| (ty, None))
+                .or_insert_with(|| (&field.ty, None))
                 .1 = bits;
         }
     }
 
+    // 自动计算的字段写入之后再检查断言
+    for (idx, field) in fields.iter().enumerate() {
+        if field.assert.is_none() || skipped.contains(&field.name) {
+            continue;
+        }
+        let siblings = preceding_values(&fields[..=idx], object, &result, &skipped, ctx);
+        if !field.holds(&siblings)? {
+            return Err(WriteBinError::AssertionFailed {
+                field: field.name.clone(),
+                expr: field.assert.clone().unwrap_or_default(),
+            });
+        }
+    }
+
     let mut bits_size = 0;
     let l = fields.iter()
-        .map(|Field { name, .. }| {
+        .map(|Field { name, ty, .. }| {
             if let Some((_, Some(v))) = result.remove(name) {
                 bits_size += v.len();
-                Ok(v)
+                Ok((ty, v))
             } else {
                 Err(WriteBinError::MissField(name.clone()))
             }
         })
         .collect::<Result<Vec<_>, _>>()?;
     let mut bits = BitVec::with_capacity(bits_size);
-    for mut v in l {
+    let mut packed = vec![];
+    for (ty, mut v) in l {
+        let (ty, ctx) = ctx.resolve(ty)?;
+        if bit_order == BitOrder::Lsb0 && packed_type(ty, &ctx).is_some() {
+            packed.push(bits.len()..bits.len() + v.len());
+        }
         bits.append(&mut v);
     }
 
+    if bit_order == BitOrder::Lsb0 {
+        bits.resize(bits.len().next_multiple_of(8), false);
+        to_lsb0(&mut bits, &packed);
+    }
     Ok(bits)
 }
 
+/// 同级字段的值，用于执行条件与断言。没有输入值的字段（如自动计算的长度）使用其写入结果读取得到的值，
+/// 计算字段使用之前字段的值计算
+fn preceding_values(
+    fields: &[Field],
+    object: &Map<String, Value>,
+    result: &FieldBits,
+    skipped: &HashSet<&String>,
+    ctx: &Context,
+) -> Map<String, Value> {
+    let mut values = Map::with_capacity(fields.len());
+    for Field { name, ty, .. } in fields {
+        if skipped.contains(name) {
+            continue;
+        }
+        let value = match (object.get(name), ty) {
+            (Some(v), _) => Some(v.clone()),
+            (None, Type::Computed { expr }) => siblings_ctx(&values)
+                .and_then(|c| evalexpr::eval_with_context(expr, &c))
+                .ok()
+                .map(to_json_value),
+            (None, _) => match result.get(name) {
+                Some((_, Some(bits))) => ctx.resolve(ty)
+                    .ok()
+                    .and_then(|(ty, ctx)| ty.read_ctx(bits, &ctx).ok())
+                    .map(|(v, _)| v),
+                _ => None,
+            },
+        };
+        if let Some(v) = value {
+            values.insert(name.clone(), v);
+        }
+    }
+    values
+}
+
 fn write_normal_field(
     ty: &Type,
     value: Option<&Value>,
     object: &Map<String, Value>,
-    result: &mut HashMap<&String, (&Type, Option<BitVec<u8, Msb0>>)>,
+    result: &mut FieldBits,
+    ctx: &Context,
 ) -> Result<Option<BitVec<u8, Msb0>>, WriteBinError> {
-    if let Type::Magic { .. } = ty {
-        return ty.write(value.unwrap_or(&Value::Null))
+    let (ty, ctx) = ctx.resolve(ty)?;
+    if let Type::Magic { .. } | Type::Padding { .. } | Type::Computed { .. } = ty {
+        return ty.write_ctx(value.unwrap_or(&Value::Null), &ctx)
             .map(|o| Some(o));
     }
 
@@ -144,18 +278,20 @@ fn write_normal_field(
             .ok_or(WriteBinError::EnumByTypeError)?;
         let ty = map.get(&key)
             .ok_or(WriteBinError::EnumError)?;
-        let out = ty.write(value)?;
+        let out = ty.write_ctx(value, &ctx)?;
         check_size(size, &out)?;
         out
     } else {
         let mut ty = ty.clone();
 
         if let
-        | Type::String { size: size @ Some(BytesSize::By(_) | BytesSize::Enum { .. }) }
-        | Type::Bin { size: size @ Some(BytesSize::By(_) | BytesSize::Enum { .. }) }
+        | Type::String { size: size @ Some(BytesSize::By(_) | BytesSize::Enum { .. }), .. }
+        | Type::Bin { size: size @ Some(BytesSize::By(_) | BytesSize::Enum { .. }), .. }
         | Type::Struct { size: size @ Some(BytesSize::By(_) | BytesSize::Enum { .. }), .. }
         | Type::Array { size: size @ Some(BytesSize::By(_) | BytesSize::Enum { .. }), .. }
+        | Type::Tlv { size: size @ Some(BytesSize::By(_) | BytesSize::Enum { .. }), .. }
         | Type::Encrypt { size: size @ Some(BytesSize::By(_) | BytesSize::Enum { .. }), .. }
+        | Type::Compressed { size: size @ Some(BytesSize::By(_) | BytesSize::Enum { .. }), .. }
         = &mut ty
         {
             *size = None;
@@ -165,19 +301,35 @@ fn write_normal_field(
             *length = None;
         }
 
-        ty.write(value)?
+        ty.write_ctx(value, &ctx)?
     };
 
     if let
-    | Type::String { size: Some(BytesSize::By(by) | BytesSize::Enum { by, .. }) }
-    | Type::Bin { size: Some(BytesSize::By(by) | BytesSize::Enum { by, .. }) }
+    | Type::String { size: Some(BytesSize::By(by) | BytesSize::Enum { by, .. }), .. }
+    | Type::Bin { size: Some(BytesSize::By(by) | BytesSize::Enum { by, .. }), .. }
     | Type::Struct { size: Some(BytesSize::By(by) | BytesSize::Enum { by, .. }), .. }
     | Type::Array { size: Some(BytesSize::By(by) | BytesSize::Enum { by, .. }), .. }
+    | Type::Tlv { size: Some(BytesSize::By(by) | BytesSize::Enum { by, .. }), .. }
     | Type::Enum { size: Some(BytesSize::By(by) | BytesSize::Enum { by, .. }), .. }
     | Type::Encrypt { size: Some(BytesSize::By(by) | BytesSize::Enum { by, .. }), .. }
+    | Type::Compressed { size: Some(BytesSize::By(by) | BytesSize::Enum { by, .. }), .. }
     = ty
     {
-        set_by_value(result, ty, &bits, by)?;
+        set_by_value(result, ty, &bits, by, &ctx)?;
+    }
+
+    if let
+    | Type::String { size: Some(BytesSize::Expr { expr }), .. }
+    | Type::Bin { size: Some(BytesSize::Expr { expr }), .. }
+    | Type::Struct { size: Some(BytesSize::Expr { expr }), .. }
+    | Type::Array { size: Some(BytesSize::Expr { expr }), .. }
+    | Type::Tlv { size: Some(BytesSize::Expr { expr }), .. }
+    | Type::Enum { size: Some(BytesSize::Expr { expr }), .. }
+    | Type::Encrypt { size: Some(BytesSize::Expr { expr }), .. }
+    | Type::Compressed { size: Some(BytesSize::Expr { expr }), .. }
+    = ty
+    {
+        set_by_expr(result, expr, bytes_len(&bits)?, object, &ctx)?;
     }
 
     if let (
@@ -185,7 +337,14 @@ fn write_normal_field(
         Some(l)
     ) = (ty, value.as_array()) {
         let (ty, out) = result.get_mut(by).ok_or(WriteBinError::ByError)?;
-        *out = Some(ty.write(&(l.len().into()))?);
+        *out = Some(write_by_field(ty, &(l.len().into()), &ctx)?);
+    }
+
+    if let (
+        Type::Array { length: Some(Length::Expr { expr }), .. },
+        Some(l)
+    ) = (ty, value.as_array()) {
+        set_by_expr(result, expr, l.len(), object, &ctx)?;
     }
 
     Ok(Some(bits))
@@ -193,22 +352,22 @@ fn write_normal_field(
 
 
 fn set_by_value(
-    result: &mut HashMap<&String, (&Type, Option<BitVec<u8, Msb0>>)>,
+    result: &mut FieldBits,
     ty: &Type,
     bits: &BitVec<u8, Msb0>,
     by: &String,
+    ctx: &Context,
 ) -> Result<(), WriteBinError> {
-    let bytes = bits.len() / 8;
-    if bits.len() % 8 != 0 {
-        return Err(WriteBinError::BytesSizeError);
-    }
+    let bytes = bytes_len(bits)?;
 
     let by_value = if let
-    | Type::String { size: Some(BytesSize::Enum { map, .. }) }
-    | Type::Bin { size: Some(BytesSize::Enum { map, .. }) }
+    | Type::String { size: Some(BytesSize::Enum { map, .. }), .. }
+    | Type::Bin { size: Some(BytesSize::Enum { map, .. }), .. }
     | Type::Struct { size: Some(BytesSize::Enum { map, .. }), .. }
     | Type::Array { size: Some(BytesSize::Enum { map, .. }), .. }
+    | Type::Tlv { size: Some(BytesSize::Enum { map, .. }), .. }
     | Type::Encrypt { size: Some(BytesSize::Enum { map, .. }), .. }
+    | Type::Compressed { size: Some(BytesSize::Enum { map, .. }), .. }
     = ty
     {
         if let Some(KeyRange::Value(k)) = map.find_key(&bytes) {
@@ -222,6 +381,91 @@ fn set_by_value(
 
 
     let (ty, out) = result.get_mut(by).ok_or(WriteBinError::ByError)?;
-    *out = Some(ty.write(&(by_value.into()))?);
+    *out = Some(write_by_field(ty, &(by_value.into()), ctx)?);
     Ok(())
 }
+
+/// 根据表达式与写入数据的大小（或数组长度）反算被引用字段的值。被引用字段均已输入值时，检查结果是否一致
+fn set_by_expr(
+    result: &mut FieldBits,
+    expr: &str,
+    input: usize,
+    object: &Map<String, Value>,
+    ctx: &Context,
+) -> Result<(), WriteBinError> {
+    let node = evalexpr::build_operator_tree(expr)?;
+    let mut idents = node.iter_variable_identifiers().collect::<Vec<_>>();
+    idents.sort_unstable();
+    idents.dedup();
+    let fields = idents.iter()
+        .map(|ident| ident.strip_prefix("self.").unwrap_or(ident))
+        .collect::<Vec<_>>();
+
+    // 与读取时相同，整数使用整型变量计算，结果必须为非负整数
+    let check = |ctx: &evalexpr::HashMapContext| -> Result<(), WriteBinError> {
+        let need = to_json_value(node.eval_with_context(ctx)?);
+        if as_u64(&need) == Some(input as u64) {
+            Ok(())
+        } else {
+            let need = need.as_f64().unwrap_or(f64::NAN);
+            Err(WriteBinError::ExprSizeError { expr: expr.to_string(), need, input })
+        }
+    };
+
+    if fields.iter().all(|f| object.contains_key(*f)) {
+        return check(&siblings_ctx(object)?);
+    }
+
+    let not_linear = || evalexpr::EvalexprError::CustomMessage(
+        format!("无法通过表达式`{}`反算引用字段的值，表达式只能是一个字段的线性表达式", expr)
+    );
+    let (ident, field) = match (idents.as_slice(), fields.as_slice()) {
+        ([ident], [field]) => (*ident, *field),
+        _ => return Err(not_linear().into()),
+    };
+    let eval = |x: f64| -> evalexpr::EvalexprResult<f64> {
+        let mut c = evalexpr::HashMapContext::new();
+        c.set_value(ident.to_string(), evalexpr::Value::Float(x))?;
+        node.eval_number_with_context(&c)
+    };
+
+    // f(x) = a * x + b
+    let b = eval(0.0)?;
+    let a = eval(1.0)? - b;
+    if a == 0.0 || (eval(2.0)? - (2.0 * a + b)).abs() > f64::EPSILON * b.abs().max(1.0) {
+        return Err(not_linear().into());
+    }
+    let x = (input as f64 - b) / a;
+    if x < 0.0 || x.fract() != 0.0 || x > i64::MAX as f64 {
+        return Err(WriteBinError::ExprSizeError { expr: expr.to_string(), need: x, input });
+    }
+    // 只在三个点上取样，需要再次计算以排除非线性的表达式
+    let mut c = evalexpr::HashMapContext::new();
+    c.set_value(ident.to_string(), evalexpr::Value::Int(x as i64))?;
+    check(&c).map_err(|_| not_linear())?;
+
+    let key = field.to_string();
+    let (ty, out) = result.get_mut(&key).ok_or(WriteBinError::ByError)?;
+    *out = Some(write_by_field(ty, &(x as u64).into(), ctx)?);
+    Ok(())
+}
+
+/// 写入自动计算的长度字段，与普通字段一样处理[`BitOrder::Lsb0`]的排列
+fn write_by_field(ty: &Type, value: &Value, ctx: &Context) -> Result<BitVec<u8, Msb0>, WriteBinError> {
+    let (ty, ctx) = ctx.resolve(ty)?;
+    match (ctx.bit_order(), packed_type(ty, &ctx)) {
+        (BitOrder::Lsb0, Some((_, packed))) => {
+            let mut bits = packed.write_ctx(value, &ctx)?;
+            bits.reverse();
+            Ok(bits)
+        }
+        _ => ty.write_ctx(value, &ctx),
+    }
+}
+
+fn bytes_len(bits: &BitVec<u8, Msb0>) -> Result<usize, WriteBinError> {
+    if bits.len() % 8 != 0 {
+        return Err(WriteBinError::BytesSizeError);
+    }
+    Ok(bits.len() / 8)
+}